@@ -0,0 +1,63 @@
+use chip8_core::Emulator;
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion, Throughput};
+
+const TICKS: u64 = 1_000_000;
+const SEED: u64 = 0xC8;
+
+// draws a 15 row sprite at a random position forever, mostly measures DXYN
+const DRAW_ROM: [u8; 25] = [
+    0xA2, 0x0A, // 200: I = 0x20A
+    0xC0, 0x3F, // 202: v0 = random & 0x3F
+    0xC1, 0x1F, // 204: v1 = random & 0x1F
+    0xD0, 0x1F, // 206: draw 15 rows at (v0, v1)
+    0x12, 0x02, // 208: jump 0x202
+    0xFF, 0x81, 0xBD, 0xA5, 0xA5, 0xBD, 0x81, 0xFF, // 20A: sprite data
+    0xFF, 0x81, 0xBD, 0xA5, 0xA5, 0xBD, 0x81,
+];
+
+// register arithmetic in a tight loop, mostly measures opcode dispatch
+const ARITHMETIC_ROM: [u8; 16] = [
+    0x60, 0x01, // 200: v0 = 1
+    0x71, 0x03, // 202: v1 += 3
+    0x82, 0x14, // 204: v2 += v1
+    0x83, 0x25, // 206: v3 -= v2
+    0x84, 0x36, // 208: v4 >>= 1
+    0x85, 0x4E, // 20A: v5 <<= 1
+    0x86, 0x53, // 20C: v6 ^= v5
+    0x12, 0x02, // 20E: jump 0x202
+];
+
+fn bench_rom(c: &mut Criterion, name: &str, rom: &[u8]) {
+    let mut group = c.benchmark_group(name);
+    // reported as ticks per second
+    group.throughput(Throughput::Elements(TICKS));
+    group.sample_size(20);
+    group.bench_function("1M ticks", |b| {
+        b.iter_batched(
+            || {
+                let mut emulator = Emulator::with_seed(SEED);
+                emulator.load(rom).unwrap();
+                emulator
+            },
+            |mut emulator| {
+                for _ in 0..TICKS {
+                    emulator.tick().unwrap();
+                }
+                emulator
+            },
+            BatchSize::LargeInput,
+        )
+    });
+    group.finish();
+}
+
+fn draw_heavy(c: &mut Criterion) {
+    bench_rom(c, "draw_heavy", &DRAW_ROM);
+}
+
+fn arithmetic_heavy(c: &mut Criterion) {
+    bench_rom(c, "arithmetic_heavy", &ARITHMETIC_ROM);
+}
+
+criterion_group!(benches, draw_heavy, arithmetic_heavy);
+criterion_main!(benches);
@@ -0,0 +1,134 @@
+// feeds random roms through the core in strict and lax mode (see Emulator::set_strict) and reports any that panic.
+// strict mode is allowed to stop with an EmulatorError and lax mode to carry on, neither may ever panic
+//
+// usage: cargo run --release --example fuzz_modes [rom count] [first seed]
+//
+// every rom comes from its own seed, so a reported seed can be rerun on its own with "fuzz_modes 1 <seed>".
+// a handful of hand picked edge case roms run first, whatever the arguments
+use chip8_core::Emulator;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::env;
+use std::panic::{self, AssertUnwindSafe};
+use std::process;
+
+const DEFAULT_ROMS: u64 = 10_000;
+const MAX_ROM_SIZE: usize = 512;
+const MAX_CYCLES: u64 = 2_000;
+const CYCLES_PER_FRAME: u64 = 10;
+const HIGH_INDEX_CHANCE: f64 = 0.125; // how often random_rom puts in ANNN near the top of ram and an I based opcode
+
+// corners random bytes hardly ever reach: I at the very top of ram before every I based opcode,
+// pc and jumps running off the end, and the stack filled or emptied
+const EDGE_ROMS: &[&[u8]] = &[
+    &[0xAF, 0xFF, 0xF0, 0x33], // FX33
+    &[0xAF, 0xFE, 0xF0, 0x33],
+    &[0xAF, 0xFF, 0xFF, 0x55], // FX55
+    &[0xAF, 0xFF, 0xFF, 0x65], // FX65
+    &[0xAF, 0xFF, 0xD0, 0x1F], // DXYN
+    &[0x6F, 0xFF, 0xAF, 0xFF, 0xFF, 0x1E, 0xF0, 0x33], // FX1E past 0xFFF, then FX33
+    &[0x60, 0xFF, 0xF0, 0x29, 0xD0, 0x15], // FX29 with a digit above F
+    &[0x60, 0xFF, 0xE0, 0x9E, 0xE0, 0xA1], // EX9E/EXA1 with a key above F
+    &[0x1F, 0xFE], // jump to the last word of ram
+    &[0x1F, 0xFF], // jump to the last byte
+    &[0x60, 0xFF, 0xBF, 0xFF], // BNNN past the end
+    &[0x22, 0x00], // calls itself until the stack overflows
+    &[0x00, 0xEE], // return with an empty stack
+];
+
+fn main() {
+    let mut args = env::args().skip(1);
+    let roms = args.next().and_then(|value| value.parse().ok()).unwrap_or(DEFAULT_ROMS);
+    let first_seed = args.next().and_then(|value| value.parse().ok()).unwrap_or(0);
+
+    // the seed report below says everything the default hook would
+    panic::set_hook(Box::new(|_| {}));
+
+    let mut failures = 0;
+    for (idx, rom) in EDGE_ROMS.iter().enumerate() {
+        failures += check(rom, 0, &format!("edge rom {}", idx));
+    }
+    for seed in first_seed..first_seed + roms {
+        failures += check(&random_rom(seed), seed, &format!("seed {}", seed));
+    }
+
+    println!("{} edge roms and {} roms from seed {}, {} panics", EDGE_ROMS.len(), roms, first_seed, failures);
+    if failures > 0 {
+        process::exit(1);
+    }
+}
+
+// runs rom in both modes, returning how many of them panicked
+fn check(rom: &[u8], seed: u64, label: &str) -> usize {
+    let mut failures = 0;
+
+    for strict in [true, false] {
+        let result = panic::catch_unwind(AssertUnwindSafe(|| run(rom, seed, strict)));
+        if let Err(panic) = result {
+            let message = panic
+                .downcast_ref::<&str>()
+                .map(|message| message.to_string())
+                .or_else(|| panic.downcast_ref::<String>().cloned())
+                .unwrap_or_default();
+            let mode = if strict { "strict" } else { "lax" };
+            println!("{}: panicked in {} mode: {}", label, mode, message);
+            failures += 1;
+        }
+    }
+
+    failures
+}
+
+// random words, cut to a random (possibly odd) length. plain random bytes almost never point I at the
+// last few bytes of ram right before an I based opcode, so some words are that pair instead
+fn random_rom(seed: u64) -> Vec<u8> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let size = rng.gen_range(2, MAX_ROM_SIZE + 1);
+
+    let mut words: Vec<u16> = Vec::new();
+    while words.len() * 2 < size {
+        if rng.gen_bool(HIGH_INDEX_CHANCE) {
+            words.push(0xA000 | rng.gen_range(0xFF0, 0x1000));
+            words.push(index_opcode(&mut rng));
+        } else {
+            words.push(rng.gen());
+        }
+    }
+
+    let mut rom: Vec<u8> = words.iter().flat_map(|word| word.to_be_bytes()).collect();
+    rom.truncate(size);
+
+    rom
+}
+
+// a random FX33, FX55, FX65, FX1E or DXYN
+fn index_opcode(rng: &mut StdRng) -> u16 {
+    let x: u16 = rng.gen_range(0, 0x10);
+    match rng.gen_range(0, 5) {
+        0 => 0xF033 | x << 8,
+        1 => 0xF055 | x << 8,
+        2 => 0xF065 | x << 8,
+        3 => 0xF01E | x << 8,
+        _ => 0xD000 | x << 8 | rng.gen_range(0, 0x100),
+    }
+}
+
+// runs until the rom errors or MAX_CYCLES, the error itself is fine. some keys are held, picked by the seed,
+// so the key opcodes get exercised too
+fn run(rom: &[u8], seed: u64, strict: bool) {
+    let mut emulator = Emulator::with_seed(seed);
+    emulator.set_strict(strict);
+    if emulator.load(rom).is_err() {
+        return;
+    }
+    emulator.set_keys(seed as u16);
+
+    for cycle in 1..=MAX_CYCLES {
+        if emulator.tick().is_err() {
+            return;
+        }
+        if cycle % CYCLES_PER_FRAME == 0 {
+            emulator.update_timers();
+        }
+    }
+}
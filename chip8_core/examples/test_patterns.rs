@@ -0,0 +1,104 @@
+// writes a few small test pattern roms for checking a front-end's display mapping and scaling
+//
+// usage: cargo run --example test_patterns [output dir]
+//
+// every rom draws its pattern once and then spins on a jump to itself
+use std::env;
+use std::fs;
+use std::path::Path;
+
+// lights every pixel by drawing 8x8 blocks of 0xFF across the screen
+const FILL: &[u8] = &[
+    0xA2, 0x18, // 0x200 A218 => I = block sprite
+    0x60, 0x00, // 0x202 6000 => v0 (x) = 0
+    0x61, 0x00, // 0x204 6100 => v1 (y) = 0
+    0xD0, 0x18, // 0x206 D018 => draw 8 rows at (v0, v1)
+    0x70, 0x08, // 0x208 7008 => x += 8
+    0x30, 0x40, // 0x20A 3040 => skip the jump once x reaches 64
+    0x12, 0x06, // 0x20C 1206 => next block in the row
+    0x60, 0x00, // 0x20E 6000 => x = 0
+    0x71, 0x08, // 0x210 7108 => y += 8
+    0x31, 0x20, // 0x212 3120 => skip the jump once y reaches 32
+    0x12, 0x06, // 0x214 1206 => next row of blocks
+    0x12, 0x16, // 0x216 1216 => done, spin
+    0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, // 0x218 block sprite
+];
+
+// same loop as FILL with a block that alternates every pixel
+const CHECKERBOARD: &[u8] = &[
+    0xA2, 0x18, // 0x200 A218 => I = checker sprite
+    0x60, 0x00, // 0x202 6000 => v0 (x) = 0
+    0x61, 0x00, // 0x204 6100 => v1 (y) = 0
+    0xD0, 0x18, // 0x206 D018 => draw 8 rows at (v0, v1)
+    0x70, 0x08, // 0x208 7008 => x += 8
+    0x30, 0x40, // 0x20A 3040 => skip the jump once x reaches 64
+    0x12, 0x06, // 0x20C 1206 => next block in the row
+    0x60, 0x00, // 0x20E 6000 => x = 0
+    0x71, 0x08, // 0x210 7108 => y += 8
+    0x31, 0x20, // 0x212 3120 => skip the jump once y reaches 32
+    0x12, 0x06, // 0x214 1206 => next row of blocks
+    0x12, 0x16, // 0x216 1216 => done, spin
+    0xAA, 0x55, 0xAA, 0x55, 0xAA, 0x55, 0xAA, 0x55, // 0x218 checker sprite
+];
+
+// the built in font 0 to F, eight digits per row on an 8px grid
+const HEX_DIGITS: &[u8] = &[
+    0x60, 0x00, // 0x200 6000 => v0 (x) = 0
+    0x61, 0x00, // 0x202 6100 => v1 (y) = 0
+    0x62, 0x00, // 0x204 6200 => v2 (digit) = 0
+    0xF2, 0x29, // 0x206 F229 => I = font glyph for v2
+    0xD0, 0x15, // 0x208 D015 => draw the 5 row glyph at (v0, v1)
+    0x72, 0x01, // 0x20A 7201 => next digit
+    0x70, 0x08, // 0x20C 7008 => x += 8
+    0x30, 0x40, // 0x20E 3040 => skip the jump once x reaches 64
+    0x12, 0x06, // 0x210 1206 => next digit in the row
+    0x60, 0x00, // 0x212 6000 => x = 0
+    0x71, 0x08, // 0x214 7108 => y += 8
+    0x32, 0x10, // 0x216 3210 => skip the jump after digit F
+    0x12, 0x06, // 0x218 1206 => next row of digits
+    0x12, 0x1A, // 0x21A 121A => done, spin
+];
+
+// a one pixel frame around the very edge of the screen
+const BORDER: &[u8] = &[
+    0xA2, 0x26, // 0x200 A226 => I = horizontal sprite
+    0x60, 0x00, // 0x202 6000 => v0 (x) = 0
+    0x61, 0x00, // 0x204 6100 => v1 (top) = 0
+    0x62, 0x1F, // 0x206 621F => v2 (bottom) = 31
+    0xD0, 0x11, // 0x208 D011 => top edge
+    0xD0, 0x21, // 0x20A D021 => bottom edge
+    0x70, 0x08, // 0x20C 7008 => x += 8
+    0x30, 0x40, // 0x20E 3040 => skip the jump once x reaches 64
+    0x12, 0x08, // 0x210 1208 => next 8px of both edges
+    0xA2, 0x27, // 0x212 A227 => I = vertical sprite
+    0x60, 0x00, // 0x214 6000 => v0 (left) = 0
+    0x61, 0x01, // 0x216 6101 => v1 (y) = 1
+    0x63, 0x3F, // 0x218 633F => v3 (right) = 63
+    0xD0, 0x1F, // 0x21A D01F => left edge rows 1 to 15
+    0xD3, 0x1F, // 0x21C D31F => right edge rows 1 to 15
+    0x61, 0x10, // 0x21E 6110 => y = 16
+    0xD0, 0x1F, // 0x220 D01F => left edge rows 16 to 30
+    0xD3, 0x1F, // 0x222 D31F => right edge rows 16 to 30
+    0x12, 0x24, // 0x224 1224 => done, spin
+    0xFF, // 0x226 horizontal sprite
+    0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, // 0x227 vertical sprite
+];
+
+const PATTERNS: [(&str, &[u8]); 4] = [
+    ("fill.ch8", FILL),
+    ("checkerboard.ch8", CHECKERBOARD),
+    ("hex_digits.ch8", HEX_DIGITS),
+    ("border.ch8", BORDER),
+];
+
+fn main() {
+    let output_dir = env::args().nth(1).unwrap_or_else(|| ".".to_string());
+    let output_dir = Path::new(&output_dir);
+    fs::create_dir_all(output_dir).expect("Unable to create output directory");
+
+    for (name, rom) in PATTERNS {
+        let path = output_dir.join(name);
+        fs::write(&path, rom).expect("Unable to write rom");
+        println!("wrote {} ({} bytes)", path.display(), rom.len());
+    }
+}
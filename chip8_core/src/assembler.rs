@@ -0,0 +1,436 @@
+// assembles a subset of Octo (.8o) source into a rom that can be passed straight to load()
+//
+// supported:
+//   : name              defines a label at the current address
+//   :const name value   defines a named constant, must come before it is used
+//   :call name / name   calls a label (2NNN), a bare label name is a call like in Octo
+//   123 0xFF 0b1010     bare numbers are emitted as data bytes
+//   clear, return (;), jump, jump0, i := / i += / i := hex, bcd, save, load, saveflags, loadflags, sprite,
+//   delay := vX, buzzer := vX, vX := / += / -= / =- / |= / &= / ^= / >>= / <<=,
+//   vX := random NN / delay / key, and "if ... then" with ==, !=, key and -key
+//
+// comments start with # and run to the end of the line
+use crate::{RAM_SIZE, START_ADDR};
+use std::collections::HashMap;
+use std::fmt;
+
+#[derive(Debug, PartialEq)]
+pub struct AssembleError {
+    pub line: usize, // 1 based source line the error was found on
+    pub message: String,
+}
+
+impl fmt::Display for AssembleError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl std::error::Error for AssembleError {}
+
+struct Token<'a> {
+    text: &'a str,
+    line: usize,
+}
+
+struct Assembler<'a> {
+    tokens: Vec<Token<'a>>,
+    position: usize,
+    rom: Vec<u8>,
+    labels: HashMap<&'a str, u16>,
+    constants: HashMap<&'a str, i32>,
+    fixups: Vec<(usize, Token<'a>)>, // opcodes whose NNN is a label that may not be defined yet
+}
+
+pub fn assemble(src: &str) -> Result<Vec<u8>, AssembleError> {
+    let tokens = src
+        .lines()
+        .enumerate()
+        .flat_map(|(i, line)| {
+            let code = line.split('#').next().unwrap_or("");
+            code.split_whitespace().map(move |text| Token { text, line: i + 1 })
+        })
+        .collect();
+
+    let mut assembler = Assembler {
+        tokens,
+        position: 0,
+        rom: Vec::new(),
+        labels: HashMap::new(),
+        constants: HashMap::new(),
+        fixups: Vec::new(),
+    };
+
+    // checked after every statement so label addresses can't run past the end of ram
+    let max_len = RAM_SIZE - START_ADDR as usize;
+    while assembler.position < assembler.tokens.len() {
+        let line = assembler.tokens[assembler.position].line;
+        assembler.statement()?;
+        if assembler.rom.len() > max_len {
+            let message = format!("the program is over the {} bytes that fit in ram", max_len);
+            return Err(AssembleError { line, message });
+        }
+    }
+    assembler.resolve_labels()?;
+
+    Ok(assembler.rom)
+}
+
+impl<'a> Assembler<'a> {
+    fn statement(&mut self) -> Result<(), AssembleError> {
+        let token = self.next("a statement")?;
+
+        match token.text {
+            ":" => {
+                let name = self.next("a label name")?;
+                let address = self.current_address();
+                if self.labels.insert(name.text, address).is_some() {
+                    return Err(error(&name, format!("label '{}' is defined twice", name.text)));
+                }
+            },
+            ":const" => {
+                let name = self.next("a constant name")?;
+                let value = self.value(-128, 0xFFF)?;
+                self.constants.insert(name.text, value);
+            },
+            ":call" => {
+                let target = self.next("a label to call")?;
+                self.emit_address(0x2000, target)?;
+            },
+            "clear" => self.emit(0x00E0),
+            "return" | ";" => self.emit(0x00EE),
+            "jump" => {
+                let target = self.next("a jump target")?;
+                self.emit_address(0x1000, target)?;
+            },
+            "jump0" => {
+                let target = self.next("a jump target")?;
+                self.emit_address(0xB000, target)?;
+            },
+            "bcd" => {
+                let x = self.register()?;
+                self.emit(0xF033 | x << 8);
+            },
+            "save" => {
+                let x = self.register()?;
+                self.emit(0xF055 | x << 8);
+            },
+            "load" => {
+                let x = self.register()?;
+                self.emit(0xF065 | x << 8);
+            },
+            "saveflags" => {
+                let x = self.register()?;
+                self.emit(0xF075 | x << 8);
+            },
+            "loadflags" => {
+                let x = self.register()?;
+                self.emit(0xF085 | x << 8);
+            },
+            "sprite" => {
+                let x = self.register()?;
+                let y = self.register()?;
+                let rows = self.value(0, 0xF)? as u16;
+                self.emit(0xD000 | x << 8 | y << 4 | rows);
+            },
+            "delay" => {
+                self.expect(":=")?;
+                let x = self.register()?;
+                self.emit(0xF015 | x << 8);
+            },
+            "buzzer" => {
+                self.expect(":=")?;
+                let x = self.register()?;
+                self.emit(0xF018 | x << 8);
+            },
+            "i" => {
+                let operator = self.next("an operator after i")?;
+                match operator.text {
+                    ":=" if self.peek() == Some("hex") => {
+                        self.position += 1;
+                        let x = self.register()?;
+                        self.emit(0xF029 | x << 8);
+                    },
+                    ":=" => {
+                        let target = self.next("an address")?;
+                        self.emit_address(0xA000, target)?;
+                    },
+                    "+=" => {
+                        let x = self.register()?;
+                        self.emit(0xF01E | x << 8);
+                    },
+                    _ => return Err(error(&operator, format!("unknown operator '{}' for i", operator.text))),
+                }
+            },
+            "if" => self.condition()?,
+            text => {
+                if let Some(x) = register_index(text) {
+                    self.register_statement(x)?;
+                } else if let Some(value) = self.lookup_value(text) {
+                    // bare numbers are data
+                    if !(-128..=0xFF).contains(&value) {
+                        return Err(error(&token, format!("byte {} is out of range", text)));
+                    }
+                    self.rom.push(value as u8);
+                } else {
+                    // anything else is a call to a label, like in Octo
+                    self.emit_address(0x2000, token)?;
+                }
+            },
+        }
+
+        Ok(())
+    }
+
+    // vX followed by an operator
+    fn register_statement(&mut self, x: u16) -> Result<(), AssembleError> {
+        let operator = self.next("an operator")?;
+
+        match operator.text {
+            ":=" => match self.peek() {
+                Some("random") => {
+                    self.position += 1;
+                    let nn = self.value(-128, 0xFF)? as u16 & 0xFF;
+                    self.emit(0xC000 | x << 8 | nn);
+                },
+                Some("delay") => {
+                    self.position += 1;
+                    self.emit(0xF007 | x << 8);
+                },
+                Some("key") => {
+                    self.position += 1;
+                    self.emit(0xF00A | x << 8);
+                },
+                Some(text) if register_index(text).is_some() => {
+                    let y = self.register()?;
+                    self.emit(0x8000 | x << 8 | y << 4);
+                },
+                _ => {
+                    let nn = self.value(-128, 0xFF)? as u16 & 0xFF;
+                    self.emit(0x6000 | x << 8 | nn);
+                },
+            },
+            "+=" if self.peek().and_then(register_index).is_none() => {
+                let nn = self.value(-128, 0xFF)? as u16 & 0xFF;
+                self.emit(0x7000 | x << 8 | nn);
+            },
+            _ => {
+                let last_digit = match operator.text {
+                    "|=" => 0x1,
+                    "&=" => 0x2,
+                    "^=" => 0x3,
+                    "+=" => 0x4,
+                    "-=" => 0x5,
+                    ">>=" => 0x6,
+                    "=-" => 0x7,
+                    "<<=" => 0xE,
+                    _ => return Err(error(&operator, format!("unknown operator '{}'", operator.text))),
+                };
+                let y = self.register()?;
+                self.emit(0x8000 | x << 8 | y << 4 | last_digit);
+            },
+        }
+
+        Ok(())
+    }
+
+    // "if <condition> then" skips the next instruction when the condition is false,
+    // so each condition assembles to the opposite skip opcode
+    fn condition(&mut self) -> Result<(), AssembleError> {
+        let x = self.register()?;
+        let operator = self.next("a comparison")?;
+
+        match operator.text {
+            "key" => self.emit(0xE0A1 | x << 8),
+            "-key" => self.emit(0xE09E | x << 8),
+            "==" | "!=" => {
+                let equal = operator.text == "==";
+                if self.peek().and_then(register_index).is_some() {
+                    let y = self.register()?;
+                    let opcode = if equal { 0x9000 } else { 0x5000 };
+                    self.emit(opcode | x << 8 | y << 4);
+                } else {
+                    let nn = self.value(-128, 0xFF)? as u16 & 0xFF;
+                    let opcode = if equal { 0x4000 } else { 0x3000 };
+                    self.emit(opcode | x << 8 | nn);
+                }
+            },
+            _ => return Err(error(&operator, format!("unknown comparison '{}'", operator.text))),
+        }
+
+        self.expect("then")
+    }
+
+    fn resolve_labels(&mut self) -> Result<(), AssembleError> {
+        for (offset, token) in self.fixups.iter() {
+            let address = match self.labels.get(token.text) {
+                Some(address) => *address,
+                None => return Err(error(token, format!("undefined label '{}'", token.text))),
+            };
+            // a label right at the end of a full ram has no 12 bit address
+            if address > 0xFFF {
+                return Err(error(token, format!("label '{}' at {:#X} is out of range", token.text, address)));
+            }
+
+            self.rom[*offset] |= (address >> 8) as u8 & 0x0F;
+            self.rom[*offset + 1] = (address & 0xFF) as u8;
+        }
+
+        Ok(())
+    }
+
+    // emits an opcode with an NNN operand, which is a number, a constant or a label
+    fn emit_address(&mut self, opcode: u16, target: Token<'a>) -> Result<(), AssembleError> {
+        match self.lookup_value(target.text) {
+            Some(address) => {
+                if !(0..=0xFFF).contains(&address) {
+                    return Err(error(&target, format!("address {} is out of range", target.text)));
+                }
+                self.emit(opcode | address as u16);
+            },
+            None => {
+                self.fixups.push((self.rom.len(), target));
+                self.emit(opcode);
+            },
+        }
+
+        Ok(())
+    }
+
+    fn emit(&mut self, opcode: u16) {
+        self.rom.extend_from_slice(&opcode.to_be_bytes());
+    }
+
+    fn current_address(&self) -> u16 {
+        START_ADDR + self.rom.len() as u16
+    }
+
+    fn register(&mut self) -> Result<u16, AssembleError> {
+        let token = self.next("a register")?;
+        register_index(token.text).ok_or_else(|| error(&token, format!("expected a register, found '{}'", token.text)))
+    }
+
+    fn value(&mut self, min: i32, max: i32) -> Result<i32, AssembleError> {
+        let token = self.next("a number")?;
+        let value = match self.lookup_value(token.text) {
+            Some(value) => value,
+            None => return Err(error(&token, format!("expected a number, found '{}'", token.text))),
+        };
+
+        if value < min || value > max {
+            return Err(error(&token, format!("{} is out of range", token.text)));
+        }
+
+        Ok(value)
+    }
+
+    fn lookup_value(&self, text: &str) -> Option<i32> {
+        parse_number(text).or_else(|| self.constants.get(text).copied())
+    }
+
+    fn expect(&mut self, expected: &str) -> Result<(), AssembleError> {
+        let token = self.next(expected)?;
+        if token.text != expected {
+            return Err(error(&token, format!("expected '{}', found '{}'", expected, token.text)));
+        }
+
+        Ok(())
+    }
+
+    fn peek(&self) -> Option<&'a str> {
+        self.tokens.get(self.position).map(|token| token.text)
+    }
+
+    fn next(&mut self, expected: &str) -> Result<Token<'a>, AssembleError> {
+        match self.tokens.get(self.position) {
+            Some(token) => {
+                self.position += 1;
+                Ok(Token { text: token.text, line: token.line })
+            },
+            None => Err(AssembleError {
+                line: self.tokens.last().map_or(1, |token| token.line),
+                message: format!("expected {}, found end of file", expected),
+            }),
+        }
+    }
+}
+
+fn error(token: &Token, message: String) -> AssembleError {
+    AssembleError { line: token.line, message }
+}
+
+// v0..vF (either case) into its register index
+fn register_index(text: &str) -> Option<u16> {
+    let mut chars = text.chars();
+    match (chars.next(), chars.next(), chars.next()) {
+        (Some('v' | 'V'), Some(digit), None) => digit.to_digit(16).map(|digit| digit as u16),
+        _ => None,
+    }
+}
+
+// decimal (optionally negative), 0x hex and 0b binary literals
+fn parse_number(text: &str) -> Option<i32> {
+    if let Some(hex) = text.strip_prefix("0x") {
+        i32::from_str_radix(hex, 16).ok()
+    } else if let Some(binary) = text.strip_prefix("0b") {
+        i32::from_str_radix(binary, 2).ok()
+    } else {
+        text.parse().ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // assembled opcodes as words, easier to read than bytes
+    fn words(src: &str) -> Vec<u16> {
+        assemble(src).unwrap().chunks(2).map(|pair| u16::from_be_bytes([pair[0], pair[1]])).collect()
+    }
+
+    #[test]
+    fn labels_are_addresses_from_the_start_address() {
+        assert_eq!(words(": start v0 := 1 : again jump again"), vec![0x6001, 0x1202]);
+    }
+
+    #[test]
+    fn constants_stand_in_for_numbers() {
+        assert_eq!(words(":const speed 7 v3 += speed i := speed"), vec![0x7307, 0xA007]);
+    }
+
+    #[test]
+    fn forward_references_are_patched_in() {
+        assert_eq!(words(": main :call draw ; : draw clear return"), vec![0x2204, 0x00EE, 0x00E0, 0x00EE]);
+    }
+
+    #[test]
+    fn undefined_label_is_an_error_on_its_line() {
+        let error = assemble("v0 := 1\njump nowhere").unwrap_err();
+
+        assert_eq!(error.line, 2);
+        assert_eq!(error.message, "undefined label 'nowhere'");
+    }
+
+    #[test]
+    fn numeric_address_past_0xfff_is_an_error() {
+        assert!(assemble("jump 0x1000").is_err());
+    }
+
+    #[test]
+    fn label_past_0xfff_is_an_error() {
+        // the jump plus 3582 data bytes fill ram exactly, so the label after them sits at 0x1000
+        let src = format!("jump end\n{}\n: end", "0 ".repeat(3582));
+        let error = assemble(&src).unwrap_err();
+
+        assert_eq!(error.line, 1);
+        assert_eq!(error.message, "label 'end' at 0x1000 is out of range");
+    }
+
+    #[test]
+    fn program_too_big_for_ram_is_an_error() {
+        let src = format!("{}\n0", "0 ".repeat(RAM_SIZE - START_ADDR as usize));
+        let error = assemble(&src).unwrap_err();
+
+        assert_eq!(error.line, 2);
+    }
+}
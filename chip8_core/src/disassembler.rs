@@ -0,0 +1,6 @@
+use crate::instruction::decode;
+
+// turns a raw opcode into a human readable mnemonic (Cowgod's chip8 reference naming)
+pub fn disassemble(operation: u16) -> String {
+    decode(operation).to_string()
+}
@@ -0,0 +1,90 @@
+use std::fmt;
+#[cfg(feature = "std")]
+use std::io;
+
+// errors returned by the emulator instead of panicking
+#[derive(Debug)]
+pub enum EmulatorError {
+    RomTooLarge { size: usize, max: usize }, // rom does not fit between START_ADDR and the end of ram
+    PcOutOfBounds { pc: u16 }, // the program counter points outside of ram
+    MisalignedPc { pc: u16 }, // the program counter is odd, so opcodes would be read off their boundaries
+    MemoryOutOfBounds { address: usize }, // an I based read or write went past the end of ram
+    StackOverflow { pc: u16 }, // 2NNN with all 16 stack slots in use
+    StackUnderflow { pc: u16 }, // 00EE with nothing on the stack to return to
+    UnknownOpcode { opcode: u16, pc: u16 }, // the rom hit an instruction the emulator doesn't implement
+    InvalidKeyIndex { index: usize }, // keys only go from 0x0 to 0xF
+    InvalidRegisterIndex { index: usize }, // v registers only go from V0 to VF
+    InvalidHexToken { token: String }, // load_hex_string found something that isn't a 4 digit hex opcode
+    InvalidTimerFrequency { hz: u32 }, // timers can't run at 0hz, or so fast a tick takes under a nanosecond
+    StartAddressInFont { addr: u16 }, // set_start_addr was given an address below the end of the fontset
+    UnknownQuirk { name: String }, // set_quirk was given a name it doesn't recognise
+    InvalidRamImage { len: usize, expected: usize }, // set_ram_image needs exactly one byte per ram address
+    EmptyRomSlot { slot: usize }, // activate_slot was asked for a slot load_slot never filled
+    RomSlotOutOfRange { slot: usize, max: usize }, // load_slot only has this many slots
+    InvalidAsciiArt { row: usize }, // set_screen_from_ascii found a bad row, or this many rows instead of 32
+    InvalidFontset { len: usize, expected: usize }, // custom fonts need 5 bytes for each of the 16 hex digits
+    InvalidPack { reason: &'static str }, // load_pack couldn't make sense of the header
+    InvalidFrame { reason: &'static str }, // decode_frame got a frame it can't show
+    #[cfg(feature = "std")]
+    Io { error: io::Error }, // load_rom_path couldn't read the file
+    #[cfg(feature = "base64")]
+    DecodeError { error: base64::DecodeError }, // load_base64 was given something that isn't base64
+}
+
+// addresses and opcodes are printed in hex, like "unknown opcode 0x8FF9 at PC 0x0204"
+impl fmt::Display for EmulatorError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            EmulatorError::RomTooLarge { size, max } => write!(f, "rom is {} bytes but only {} fit in ram", size, max),
+            EmulatorError::PcOutOfBounds { pc } => write!(f, "PC {:#06X} is outside of ram", pc),
+            EmulatorError::MisalignedPc { pc } => write!(f, "PC {:#06X} is not aligned to an instruction", pc),
+            EmulatorError::MemoryOutOfBounds { address } => write!(f, "memory access at {:#06X} is outside of ram", address),
+            EmulatorError::StackOverflow { pc } => write!(f, "stack overflow at PC {:#06X}", pc),
+            EmulatorError::StackUnderflow { pc } => write!(f, "return with an empty stack at PC {:#06X}", pc),
+            EmulatorError::UnknownOpcode { opcode, pc } => write!(f, "unknown opcode {:#06X} at PC {:#06X}", opcode, pc),
+            EmulatorError::InvalidKeyIndex { index } => write!(f, "key {:#X} is out of range, keys go from 0x0 to 0xF", index),
+            EmulatorError::InvalidRegisterIndex { index } => write!(f, "register V{:X} doesn't exist, registers go from V0 to VF", index),
+            EmulatorError::InvalidHexToken { token } => write!(f, "'{}' is not a 4 digit hex opcode", token),
+            EmulatorError::InvalidTimerFrequency { hz } => write!(f, "timer frequency has to be between 1hz and 1GHz, got {}hz", hz),
+            EmulatorError::StartAddressInFont { addr } => write!(f, "start address {:#06X} is inside the fontset", addr),
+            EmulatorError::UnknownQuirk { name } => write!(f, "unknown quirk '{}'", name),
+            EmulatorError::InvalidRamImage { len, expected } => write!(f, "ram image is {} bytes, expected {}", len, expected),
+            EmulatorError::EmptyRomSlot { slot } => write!(f, "rom slot {} is empty", slot),
+            EmulatorError::RomSlotOutOfRange { slot, max } => write!(f, "rom slot {} is out of range, there are {} slots", slot, max),
+            EmulatorError::InvalidAsciiArt { row } => write!(f, "ascii art has to be 32 rows of 64 '#' or '.', row {} isn't", row),
+            EmulatorError::InvalidFontset { len, expected } => write!(f, "fontset is {} bytes, expected {}", len, expected),
+            EmulatorError::InvalidPack { reason } => write!(f, "invalid rom pack, {}", reason),
+            EmulatorError::InvalidFrame { reason } => write!(f, "invalid display frame, {}", reason),
+            #[cfg(feature = "std")]
+            EmulatorError::Io { error } => write!(f, "couldn't read rom: {}", error),
+            #[cfg(feature = "base64")]
+            EmulatorError::DecodeError { error } => write!(f, "couldn't decode base64 rom: {}", error),
+        }
+    }
+}
+
+impl std::error::Error for EmulatorError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            #[cfg(feature = "std")]
+            EmulatorError::Io { error } => Some(error),
+            #[cfg(feature = "base64")]
+            EmulatorError::DecodeError { error } => Some(error),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl From<io::Error> for EmulatorError {
+    fn from(error: io::Error) -> Self {
+        EmulatorError::Io { error }
+    }
+}
+
+#[cfg(feature = "base64")]
+impl From<base64::DecodeError> for EmulatorError {
+    fn from(error: base64::DecodeError) -> Self {
+        EmulatorError::DecodeError { error }
+    }
+}
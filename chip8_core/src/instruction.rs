@@ -0,0 +1,241 @@
+use std::fmt;
+
+// a decoded opcode with its operands pulled out, x and y are register indexes
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Instruction {
+    Nop,                                // 0000
+    ClearScreen,                        // 00E0
+    Return,                             // 00EE
+    Jump { nnn: u16 },                  // 1NNN
+    Call { nnn: u16 },                  // 2NNN
+    SkipIfEq { x: usize, nn: u8 },      // 3XNN
+    SkipIfNotEq { x: usize, nn: u8 },   // 4XNN
+    SkipIfRegEq { x: usize, y: usize }, // 5XY0
+    Set { x: usize, nn: u8 },           // 6XNN
+    Add { x: usize, nn: u8 },           // 7XNN
+    SetReg { x: usize, y: usize },      // 8XY0
+    Or { x: usize, y: usize },          // 8XY1
+    And { x: usize, y: usize },         // 8XY2
+    Xor { x: usize, y: usize },         // 8XY3
+    AddReg { x: usize, y: usize },      // 8XY4
+    Sub { x: usize, y: usize },         // 8XY5
+    ShiftRight { x: usize, y: usize },  // 8XY6
+    SubReverse { x: usize, y: usize },  // 8XY7
+    ShiftLeft { x: usize, y: usize },   // 8XYE
+    SkipIfRegNotEq { x: usize, y: usize }, // 9XY0
+    SetIndex { nnn: u16 },              // ANNN
+    JumpOffset { nnn: u16 },            // BNNN
+    Random { x: usize, nn: u8 },        // CXNN
+    Draw { x: usize, y: usize, n: u8 }, // DXYN
+    SkipIfKey { x: usize },             // EX9E
+    SkipIfNotKey { x: usize },          // EXA1
+    GetDelay { x: usize },              // FX07
+    WaitKey { x: usize },               // FX0A
+    SetDelay { x: usize },              // FX15
+    SetSound { x: usize },              // FX18
+    AddIndex { x: usize },              // FX1E
+    FontChar { x: usize },              // FX29
+    Bcd { x: usize },                   // FX33
+    Store { x: usize },                 // FX55
+    Load { x: usize },                  // FX65
+    SaveFlags { x: usize },             // FX75 (SUPER-CHIP)
+    LoadFlags { x: usize },             // FX85 (SUPER-CHIP)
+    SelectPlanes { mask: u8 },          // FN01 (XO-CHIP)
+    Unknown { opcode: u16 },            // anything else, most likely data
+}
+
+// broad groups of instructions for profilers and front-ends
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum OpClass {
+    Draw,       // 00E0, DXYN, FN01
+    Flow,       // jumps, calls, returns and register skips
+    Arithmetic, // 6XNN, 7XNN, 8XYN and CXNN
+    Memory,     // anything working with I
+    Input,      // key skips and FX0A
+    Timer,      // FX07, FX15
+    Sound,      // FX18
+    System,     // 0000
+    Unknown,
+}
+
+impl Instruction {
+    pub fn class(&self) -> OpClass {
+        match self {
+            Instruction::ClearScreen | Instruction::Draw { .. } | Instruction::SelectPlanes { .. } => OpClass::Draw,
+            Instruction::Return
+            | Instruction::Jump { .. }
+            | Instruction::Call { .. }
+            | Instruction::JumpOffset { .. }
+            | Instruction::SkipIfEq { .. }
+            | Instruction::SkipIfNotEq { .. }
+            | Instruction::SkipIfRegEq { .. }
+            | Instruction::SkipIfRegNotEq { .. } => OpClass::Flow,
+            Instruction::Set { .. }
+            | Instruction::Add { .. }
+            | Instruction::SetReg { .. }
+            | Instruction::Or { .. }
+            | Instruction::And { .. }
+            | Instruction::Xor { .. }
+            | Instruction::AddReg { .. }
+            | Instruction::Sub { .. }
+            | Instruction::ShiftRight { .. }
+            | Instruction::SubReverse { .. }
+            | Instruction::ShiftLeft { .. }
+            | Instruction::Random { .. } => OpClass::Arithmetic,
+            Instruction::SetIndex { .. }
+            | Instruction::AddIndex { .. }
+            | Instruction::FontChar { .. }
+            | Instruction::Bcd { .. }
+            | Instruction::Store { .. }
+            | Instruction::Load { .. }
+            | Instruction::SaveFlags { .. }
+            | Instruction::LoadFlags { .. } => OpClass::Memory,
+            Instruction::SkipIfKey { .. } | Instruction::SkipIfNotKey { .. } | Instruction::WaitKey { .. } => OpClass::Input,
+            Instruction::GetDelay { .. } | Instruction::SetDelay { .. } => OpClass::Timer,
+            Instruction::SetSound { .. } => OpClass::Sound,
+            Instruction::Nop => OpClass::System,
+            Instruction::Unknown { .. } => OpClass::Unknown,
+        }
+    }
+}
+
+// which group an opcode belongs to without executing it
+pub fn classify_opcode(operation: u16) -> OpClass {
+    decode(operation).class()
+}
+
+// splits an opcode into its nibbles and works out which instruction it is
+pub fn decode(operation: u16) -> Instruction {
+    let digit1 = (operation & 0xF000) >> 12;
+    let digit2 = (operation & 0x0F00) >> 8;
+    let digit3 = (operation & 0x00F0) >> 4;
+    let digit4 = operation & 0x000F;
+
+    let x = digit2 as usize;
+    let y = digit3 as usize;
+    let n = digit4 as u8;
+    let nn = (operation & 0xFF) as u8;
+    let nnn = operation & 0xFFF;
+
+    match (digit1, digit2, digit3, digit4) {
+        (0, 0, 0, 0) => Instruction::Nop,
+        (0, 0, 0xE, 0) => Instruction::ClearScreen,
+        (0, 0, 0xE, 0xE) => Instruction::Return,
+        (1, _, _, _) => Instruction::Jump { nnn },
+        (2, _, _, _) => Instruction::Call { nnn },
+        (3, _, _, _) => Instruction::SkipIfEq { x, nn },
+        (4, _, _, _) => Instruction::SkipIfNotEq { x, nn },
+        (5, _, _, 0) => Instruction::SkipIfRegEq { x, y },
+        (6, _, _, _) => Instruction::Set { x, nn },
+        (7, _, _, _) => Instruction::Add { x, nn },
+        (8, _, _, 0) => Instruction::SetReg { x, y },
+        (8, _, _, 1) => Instruction::Or { x, y },
+        (8, _, _, 2) => Instruction::And { x, y },
+        (8, _, _, 3) => Instruction::Xor { x, y },
+        (8, _, _, 4) => Instruction::AddReg { x, y },
+        (8, _, _, 5) => Instruction::Sub { x, y },
+        (8, _, _, 6) => Instruction::ShiftRight { x, y },
+        (8, _, _, 7) => Instruction::SubReverse { x, y },
+        (8, _, _, 0xE) => Instruction::ShiftLeft { x, y },
+        (9, _, _, 0) => Instruction::SkipIfRegNotEq { x, y },
+        (0xA, _, _, _) => Instruction::SetIndex { nnn },
+        (0xB, _, _, _) => Instruction::JumpOffset { nnn },
+        (0xC, _, _, _) => Instruction::Random { x, nn },
+        (0xD, _, _, _) => Instruction::Draw { x, y, n },
+        (0xE, _, 9, 0xE) => Instruction::SkipIfKey { x },
+        (0xE, _, 0xA, 1) => Instruction::SkipIfNotKey { x },
+        (0xF, _, 0, 7) => Instruction::GetDelay { x },
+        (0xF, _, 0, 0xA) => Instruction::WaitKey { x },
+        (0xF, _, 1, 5) => Instruction::SetDelay { x },
+        (0xF, _, 1, 8) => Instruction::SetSound { x },
+        (0xF, _, 1, 0xE) => Instruction::AddIndex { x },
+        (0xF, _, 2, 9) => Instruction::FontChar { x },
+        (0xF, _, 3, 3) => Instruction::Bcd { x },
+        (0xF, _, 5, 5) => Instruction::Store { x },
+        (0xF, _, 6, 5) => Instruction::Load { x },
+        (0xF, _, 7, 5) => Instruction::SaveFlags { x },
+        (0xF, _, 8, 5) => Instruction::LoadFlags { x },
+        (0xF, _, 0, 1) => Instruction::SelectPlanes { mask: digit2 as u8 },
+        (_, _, _, _) => Instruction::Unknown { opcode: operation },
+    }
+}
+
+// mnemonics follow Cowgod's chip8 reference
+impl fmt::Display for Instruction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Instruction::Nop => write!(f, "NOP"),
+            Instruction::ClearScreen => write!(f, "CLS"),
+            Instruction::Return => write!(f, "RET"),
+            Instruction::Jump { nnn } => write!(f, "JP {:#05X}", nnn),
+            Instruction::Call { nnn } => write!(f, "CALL {:#05X}", nnn),
+            Instruction::SkipIfEq { x, nn } => write!(f, "SE V{:X}, {:#04X}", x, nn),
+            Instruction::SkipIfNotEq { x, nn } => write!(f, "SNE V{:X}, {:#04X}", x, nn),
+            Instruction::SkipIfRegEq { x, y } => write!(f, "SE V{:X}, V{:X}", x, y),
+            Instruction::Set { x, nn } => write!(f, "LD V{:X}, {:#04X}", x, nn),
+            Instruction::Add { x, nn } => write!(f, "ADD V{:X}, {:#04X}", x, nn),
+            Instruction::SetReg { x, y } => write!(f, "LD V{:X}, V{:X}", x, y),
+            Instruction::Or { x, y } => write!(f, "OR V{:X}, V{:X}", x, y),
+            Instruction::And { x, y } => write!(f, "AND V{:X}, V{:X}", x, y),
+            Instruction::Xor { x, y } => write!(f, "XOR V{:X}, V{:X}", x, y),
+            Instruction::AddReg { x, y } => write!(f, "ADD V{:X}, V{:X}", x, y),
+            Instruction::Sub { x, y } => write!(f, "SUB V{:X}, V{:X}", x, y),
+            Instruction::ShiftRight { x, y } => write!(f, "SHR V{:X}, V{:X}", x, y),
+            Instruction::SubReverse { x, y } => write!(f, "SUBN V{:X}, V{:X}", x, y),
+            Instruction::ShiftLeft { x, y } => write!(f, "SHL V{:X}, V{:X}", x, y),
+            Instruction::SkipIfRegNotEq { x, y } => write!(f, "SNE V{:X}, V{:X}", x, y),
+            Instruction::SetIndex { nnn } => write!(f, "LD I, {:#05X}", nnn),
+            Instruction::JumpOffset { nnn } => write!(f, "JP V0, {:#05X}", nnn),
+            Instruction::Random { x, nn } => write!(f, "RND V{:X}, {:#04X}", x, nn),
+            Instruction::Draw { x, y, n } => write!(f, "DRW V{:X}, V{:X}, {}", x, y, n),
+            Instruction::SkipIfKey { x } => write!(f, "SKP V{:X}", x),
+            Instruction::SkipIfNotKey { x } => write!(f, "SKNP V{:X}", x),
+            Instruction::GetDelay { x } => write!(f, "LD V{:X}, DT", x),
+            Instruction::WaitKey { x } => write!(f, "LD V{:X}, K", x),
+            Instruction::SetDelay { x } => write!(f, "LD DT, V{:X}", x),
+            Instruction::SetSound { x } => write!(f, "LD ST, V{:X}", x),
+            Instruction::AddIndex { x } => write!(f, "ADD I, V{:X}", x),
+            Instruction::FontChar { x } => write!(f, "LD F, V{:X}", x),
+            Instruction::Bcd { x } => write!(f, "LD B, V{:X}", x),
+            Instruction::Store { x } => write!(f, "LD [I], V{:X}", x),
+            Instruction::Load { x } => write!(f, "LD V{:X}, [I]", x),
+            Instruction::SaveFlags { x } => write!(f, "LD R, V{:X}", x),
+            Instruction::LoadFlags { x } => write!(f, "LD V{:X}, R", x),
+            Instruction::SelectPlanes { mask } => write!(f, "PLANE {}", mask),
+            Instruction::Unknown { opcode } => write!(f, "DW {:#06X}", opcode),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn opcodes_fall_into_their_class() {
+        let cases = [
+            (0x00E0, OpClass::Draw),
+            (0xD125, OpClass::Draw),
+            (0xF201, OpClass::Draw),
+            (0x00EE, OpClass::Flow),
+            (0x1234, OpClass::Flow),
+            (0x9120, OpClass::Flow),
+            (0x6A0F, OpClass::Arithmetic),
+            (0x8014, OpClass::Arithmetic),
+            (0xC3FF, OpClass::Arithmetic),
+            (0xA300, OpClass::Memory),
+            (0xF233, OpClass::Memory),
+            (0xE19E, OpClass::Input),
+            (0xF30A, OpClass::Input),
+            (0xF107, OpClass::Timer),
+            (0xF118, OpClass::Sound),
+            (0x0000, OpClass::System),
+            (0x8FF9, OpClass::Unknown),
+            (0xFFFF, OpClass::Unknown),
+        ];
+
+        for (opcode, class) in cases {
+            assert_eq!(classify_opcode(opcode), class, "{:#06X}", opcode);
+        }
+    }
+}
@@ -1,13 +1,72 @@
-use rand::Rng;
-use std::io::BufReader;
+use rand::{Rng, SeedableRng};
+use rand_pcg::Pcg32;
+
+// re-exported so set_rng callers implement the same trait version the core was built against
+pub use rand::RngCore;
+use std::collections::{HashSet, VecDeque};
+use std::fmt::Write;
+#[cfg(feature = "std")]
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+#[cfg(feature = "assembler")]
+pub mod assembler;
+pub mod disassembler;
+mod error;
+mod instruction;
+
+pub use error::EmulatorError;
+pub use instruction::{classify_opcode, decode, Instruction, OpClass};
+
+// log macros, these compile away to nothing unless the "log" feature is enabled
+macro_rules! trace {
+    ($($arg:tt)*) => {
+        #[cfg(feature = "log")]
+        log::trace!($($arg)*);
+        #[cfg(not(feature = "log"))]
+        if false {
+            let _ = format_args!($($arg)*);
+        }
+    };
+}
+
+macro_rules! warn {
+    ($($arg:tt)*) => {
+        #[cfg(feature = "log")]
+        log::warn!($($arg)*);
+        #[cfg(not(feature = "log"))]
+        if false {
+            let _ = format_args!($($arg)*);
+        }
+    };
+}
 
 const RAM_SIZE: usize = 4096; // 4KB
 const NUM_REGISTERS: usize = 16;
 const STACK_SIZE: usize = 16;
 const NUM_KEYS: usize = 16;
+const PACK_MAGIC: [u8; 4] = *b"C8PK"; // start of a load_pack rom + font bundle
+const PACK_HEADER_SIZE: usize = 8;
+const FRAME_VERSION: u8 = 1; // first byte of encode_frame, bumped when the layout changes
+const FRAME_HEADER_SIZE: usize = 4;
+const NUM_RPL_FLAGS: usize = 8; // SUPER-CHIP user flags, the HP48 kept these between runs
 pub const SCREEN_WIDTH: usize = 64; // chip8 standard width resulution => 64
 pub const SCREEN_HEIGHT: usize = 32; // chip8 standard height resulution => 32
 const START_ADDR: u16 = 0x200; // 512'th index, from where the program starts
+const TIMER_FREQUENCY: u32 = 60; // hz, how often the delay and sound timers count down
+const CRASH_TRACE_LENGTH: usize = 16; // instructions kept for the crash report
+const IPS_SAMPLE_TIME: Duration = Duration::from_millis(50); // how long estimate_ips runs for
+const CYCLES_PER_FRAME: u64 = 10; // instructions per timer tick in run_rom_to_completion, roughly a 600hz cpu
+const REWIND_FRAMES: usize = 60; // frame snapshots kept for step_back_frame, one second at 60hz
+const MAX_ROM_SLOTS: usize = 256; // load_slot slots, enough for a long playlist without a huge index growing the list
+
+// hex keys as they sit on the COSMAC VIP keypad, row by row
+const KEYPAD_LAYOUT: [[u8; 4]; 4] = [
+    [0x1, 0x2, 0x3, 0xC],
+    [0x4, 0x5, 0x6, 0xD],
+    [0x7, 0x8, 0x9, 0xE],
+    [0xA, 0x0, 0xB, 0xF],
+];
 
 // commonly used characters
 const FONTSET_SIZE: usize = 80;
@@ -30,32 +89,293 @@ const FONTSET: [u8; FONTSET_SIZE] = [
     0xF0, 0x80, 0xF0, 0x80, 0x80  // F
 ];
 
+// behaviours that differ between chip8 interpreters, all off by default apart from sprite wrapping
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Quirks {
+    pub shift_vy: bool, // 8XY6/8XYE shift vY into vX instead of shifting vX in place (COSMAC VIP)
+    pub jump_vx: bool, // BNNN jumps to XNN + vX instead of NNN + v0 (CHIP-48/SUPER-CHIP)
+    pub increment_i: bool, // FX55/FX65 leave I pointing past the last register they touched (COSMAC VIP)
+    pub vf_reset: bool, // 8XY1/8XY2/8XY3 reset vF to 0 (COSMAC VIP)
+    pub vblank: bool, // DXYN draws at most one sprite per frame, waiting for the next update_timers (COSMAC VIP)
+    pub wrap_x: bool, // DXYN wraps pixels past the left/right edge around, clips them when false
+    pub wrap_y: bool, // DXYN wraps pixels past the top/bottom edge around, clips them when false
+    pub signed_coords: bool, // DXYN reads vX/vY as i8, so 0xFD starts the sprite at -3 (only visible on a clipped axis)
+}
+
+// sprites wrap on both axes unless told otherwise
+impl Default for Quirks {
+    fn default() -> Self {
+        Quirks {
+            shift_vy: false,
+            jump_vx: false,
+            increment_i: false,
+            vf_reset: false,
+            vblank: false,
+            wrap_x: true,
+            wrap_y: true,
+            signed_coords: false,
+        }
+    }
+}
+
+impl Quirks {
+    // sets a quirk by its field name, "clip" is the opposite of wrapping on both axes
+    pub fn set(&mut self, name: &str, value: bool) -> Result<(), EmulatorError> {
+        match name {
+            "shift_vy" => self.shift_vy = value,
+            "jump_vx" => self.jump_vx = value,
+            "increment_i" => self.increment_i = value,
+            "vf_reset" => self.vf_reset = value,
+            "vblank" => self.vblank = value,
+            "clip" => {
+                self.wrap_x = !value;
+                self.wrap_y = !value;
+            },
+            "wrap_x" => self.wrap_x = value,
+            "wrap_y" => self.wrap_y = value,
+            "signed_coords" => self.signed_coords = value,
+            _ => return Err(EmulatorError::UnknownQuirk { name: name.to_string() }),
+        }
+
+        Ok(())
+    }
+
+    // (name, value) of every quirk, the names set takes, e.g. for writing them to a config file
+    pub fn named(&self) -> [(&'static str, bool); 8] {
+        [
+            ("shift_vy", self.shift_vy),
+            ("jump_vx", self.jump_vx),
+            ("increment_i", self.increment_i),
+            ("vf_reset", self.vf_reset),
+            ("vblank", self.vblank),
+            ("wrap_x", self.wrap_x),
+            ("wrap_y", self.wrap_y),
+            ("signed_coords", self.signed_coords),
+        ]
+    }
+}
+
+// what to do when an I based access runs past the end of ram
+#[derive(Clone, Copy, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum MemoryPolicy {
+    #[default]
+    Error, // stop with EmulatorError::MemoryOutOfBounds
+    Wrap, // wrap the address back around to the start of ram, undefined on real hardware so this is a best guess
+}
+
+// how DXYN combines a sprite with the screen
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum DrawMode {
+    #[default]
+    Xor, // standard, drawing over a lit pixel turns it off and sets vF
+    Or, // non-standard, sprites only ever turn pixels on and vF is always 0
+}
+
+// byte order opcodes are stored in, standard chip8 is big endian
+#[derive(Clone, Copy, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Endianness {
+    #[default]
+    Big,
+    Little, // non-standard, only for experimenting with variants that store the low byte first
+}
+
+// what a rom expects the machine to look like when it starts, see prepare_for_rom
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CompatProfile {
+    // COSMAC VIP: the interpreter owns everything below the start address, so roms only find the
+    // font there, the rest reads as zero. v registers, I, the stack and both timers start at zero
+    Vip,
+    // roms that stash data between the font and the start address (loaded with set_ram_image or
+    // written by an earlier program) and expect it to survive. registers are still zeroed
+    KeepLowRam,
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Emulator {
     program_counter: u16, // keep track of the current program instruction
+    #[cfg_attr(feature = "serde", serde(with = "serde_big_array::BigArray"))]
     ram: [u8; RAM_SIZE],
+    #[cfg_attr(feature = "serde", serde(with = "serde_big_array::BigArray"))]
     screen: [bool; SCREEN_WIDTH * SCREEN_HEIGHT],
+    #[cfg_attr(feature = "serde", serde(with = "serde_big_array::BigArray"))]
+    front_screen: [bool; SCREEN_WIDTH * SCREEN_HEIGHT], // last presented frame, only shown when double buffered
+    double_buffered: bool, // get_display shows front_screen until present_frame instead of the live screen
+    #[cfg_attr(feature = "serde", serde(with = "serde_big_array::BigArray"))]
+    plane2: [bool; SCREEN_WIDTH * SCREEN_HEIGHT], // XO-CHIP's second plane, only drawn to once FN01 selects it
+    plane_mask: u8, // planes selected by FN01, bit 0 is screen and bit 1 is plane2. 00E0 and DXYN only touch these
     v_registers: [u8; NUM_REGISTERS], // used by the game because its faster than reading from RAM
     i_register: u16, // used for indexing into RAM reads and writes
     stack_pointer: u16, // keeps track of the top of the stack
     stack: [u16; STACK_SIZE], // works on the "Lat in, first out" principe
     keys: [bool; NUM_KEYS], // keeps track of which keys are pressed
+    key_press_order: [u64; NUM_KEYS], // value of key_press_counter when each key was last pressed
+    key_press_counter: u64, // goes up on every key press so FX0A can find the newest one
     delay_timer: u8, // used as a timer, performing an action when it hits 0
-    sound_timer: u8, // counts down every cycle, emitting a noise when it hits 0
+    sound_timer: u8, // counts down every cycle, a tone plays while it is above 0
+    sound_enabled: bool, // false mutes is_beeping without stopping the sound timer, kept across resets
+    quirks: Quirks,
+    memory_policy: MemoryPolicy,
+    draw_mode: DrawMode,
+    strict_alignment: bool, // fetch errors on an odd program counter instead of reading across instructions
+    strict: bool, // unknown opcodes and stack over/underflow are errors, otherwise they are logged and skipped
+    start_addr: u16, // where roms are loaded and execution begins, START_ADDR unless configured
+    timer_frequency: u32, // hz used by update_timers_elapsed
+    timer_elapsed: Duration, // time passed to update_timers_elapsed that hasn't counted down a timer yet
+    instruction_budget: f64, // instructions per run_budgeted_frame, fractions carry over, kept across resets
+    budget_remainder: f64, // part of an instruction run_budgeted_frame still owes
+    fetch_endianness: Endianness,
+    cycle_count: u64, // number of instructions executed since power on
+    draw_calls: u64, // DXYN instructions that drew, vblank retries not included
+    instructions_this_frame: u32, // instructions executed since the last update_timers call
+    last_tick_drew: bool, // the most recent instruction changed at least one pixel
+    skipped_addr: Option<u16>, // where the opcode the most recent instruction skipped over is
+    last_opcode: u16, // most recently executed opcode, 0 until the first tick
+    drew_this_frame: bool, // a DXYN ran since the last update_timers call, used by the vblank quirk
+    watched_addrs: Vec<u16>, // ram addresses reported by take_watch_hits when written
+    detect_smc: bool, // report fetches from ram the rom wrote itself, kept across resets
+    #[cfg_attr(feature = "serde", serde(with = "serde_big_array::BigArray"))]
+    rom_written: [u64; RAM_SIZE / 64], // one bit per ram address stored to by FX33/FX55, only kept while detect_smc is on
+    smc_events: Vec<u16>, // addresses execution fetched self modified code from, each listed once
+    cheats: Vec<(u16, u8)>, // (address, value) written back to ram after every tick
+    rpl_flags: [u8; NUM_RPL_FLAGS], // written by FX75 and read by FX85, kept across resets
+    #[cfg_attr(feature = "serde", serde(with = "serde_big_array::BigArray"))]
+    fontset: [u8; FONTSET_SIZE], // glyphs copied to the start of ram on reset, FONTSET unless set_fontset was used
+    #[cfg_attr(feature = "serde", serde(skip))]
+    input_recording: Option<Vec<u16>>, // keys_mask at every update_timers while recording
+    #[cfg_attr(feature = "serde", serde(skip))]
+    input_playback: VecDeque<u16>, // recorded frames still to be replayed, one per update_timers
+    frame_rewind: bool, // update_timers keeps frame_snapshots for step_back_frame, kept across resets
+    #[cfg_attr(feature = "serde", serde(skip))]
+    frame_snapshots: VecDeque<FrameSnapshot>, // newest last, at most REWIND_FRAMES
+    #[cfg_attr(feature = "serde", serde(skip))]
+    rom_slots: Vec<Vec<u8>>, // roms preloaded with load_slot, empty until filled
+    watch_hits: Vec<(u64, u16, u8)>, // (cycle, address, new value)
+    rom_len: usize, // size of the last loaded rom
+    rom_checksum: u64, // fnv-1a of the last loaded rom, taken in load before the rom can modify itself
+    seed: Option<u64>, // set by with_seed, CXNN uses thread_rng when None
+    rng: Option<Pcg32>, // seeded from seed, serialized so a restored emulator rolls the same numbers
+    #[cfg_attr(feature = "serde", serde(skip))]
+    user_rng: Option<Box<dyn RngCore + Send>>, // set_rng, used by CXNN before the seeded rng, kept across resets
+    #[cfg(feature = "std")]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    crash_dump_path: Option<PathBuf>, // where tick writes a crash report when it returns an error
+    #[cfg(feature = "std")]
+    max_ips: Option<u32>, // cap for the run loops that own the timing, kept across resets
+    #[cfg(feature = "std")]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    ips_window: Option<(Instant, u64)>, // when the capped run started and how many instructions it has run since
+    #[cfg_attr(feature = "serde", serde(skip))]
+    recent_trace: VecDeque<(u16, u16)>, // last (pc, opcode) pairs, only recorded while a crash dump path is set
+}
+
+// machine state at a frame boundary, keys are left out so stepping back doesn't fight the player's hands
+#[derive(Clone)]
+struct FrameSnapshot {
+    program_counter: u16,
+    ram: [u8; RAM_SIZE],
+    screen: [bool; SCREEN_WIDTH * SCREEN_HEIGHT],
+    front_screen: [bool; SCREEN_WIDTH * SCREEN_HEIGHT],
+    plane2: [bool; SCREEN_WIDTH * SCREEN_HEIGHT],
+    plane_mask: u8,
+    v_registers: [u8; NUM_REGISTERS],
+    i_register: u16,
+    stack_pointer: u16,
+    stack: [u16; STACK_SIZE],
+    delay_timer: u8,
+    sound_timer: u8,
+    cycle_count: u64,
+    draw_calls: u64,
+    rpl_flags: [u8; NUM_RPL_FLAGS],
+    rng: Option<Pcg32>, // so CXNN rolls the same numbers again after stepping back
+}
+
+// everything one single_cycle did, for step by step visualisers
+#[derive(Clone, Debug, PartialEq)]
+pub struct CycleReport {
+    pub pc: u16, // where the opcode was fetched from
+    pub opcode: u16,
+    pub instruction: Instruction,
+    pub changed_registers: Vec<usize>, // v registers that hold a different value now, vF included when a flag was set
+    pub changed_memory: Vec<u16>, // ram addresses that hold a different value now
+    pub i_changed: bool,
+    pub screen_changed: bool,
+}
+
+impl Default for Emulator {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Emulator {
+    // starts strict about errors and memory but, unlike set_strict(true), reads odd pcs since chip8 doesn't
+    // require aligned opcodes. set_strict picks one of the two full states
     pub fn new() -> Self {
         let mut new_emulator = Self {
             program_counter: START_ADDR,
             ram: [0; RAM_SIZE],
             screen: [false; SCREEN_WIDTH * SCREEN_HEIGHT],
+            front_screen: [false; SCREEN_WIDTH * SCREEN_HEIGHT],
+            double_buffered: false,
+            plane2: [false; SCREEN_WIDTH * SCREEN_HEIGHT],
+            plane_mask: 0b01,
             v_registers: [0; NUM_REGISTERS],
             i_register: 0,
             stack_pointer: 0,
             stack: [0; STACK_SIZE],
             keys: [false; NUM_KEYS],
+            key_press_order: [0; NUM_KEYS],
+            key_press_counter: 0,
             delay_timer: 0,
             sound_timer: 0,
+            quirks: Quirks::default(),
+            memory_policy: MemoryPolicy::default(),
+            draw_mode: DrawMode::default(),
+            strict_alignment: false,
+            strict: true,
+            sound_enabled: true,
+            start_addr: START_ADDR,
+            timer_frequency: TIMER_FREQUENCY,
+            timer_elapsed: Duration::ZERO,
+            instruction_budget: CYCLES_PER_FRAME as f64,
+            budget_remainder: 0.0,
+            fetch_endianness: Endianness::default(),
+            cycle_count: 0,
+            draw_calls: 0,
+            instructions_this_frame: 0,
+            last_tick_drew: false,
+            skipped_addr: None,
+            last_opcode: 0,
+            drew_this_frame: false,
+            watched_addrs: Vec::new(),
+            cheats: Vec::new(),
+            rpl_flags: [0; NUM_RPL_FLAGS],
+            fontset: FONTSET,
+            input_recording: None,
+            input_playback: VecDeque::new(),
+            detect_smc: false,
+            rom_written: [0; RAM_SIZE / 64],
+            smc_events: Vec::new(),
+            frame_rewind: false,
+            frame_snapshots: VecDeque::new(),
+            rom_slots: Vec::new(),
+            watch_hits: Vec::new(),
+            rom_len: 0,
+            rom_checksum: 0,
+            seed: None,
+            rng: None,
+            user_rng: None,
+            #[cfg(feature = "std")]
+            crash_dump_path: None,
+            #[cfg(feature = "std")]
+            max_ips: None,
+            #[cfg(feature = "std")]
+            ips_window: None,
+            recent_trace: VecDeque::new(),
         };
 
         // load the defualt characters into ram
@@ -65,422 +385,3585 @@ impl Emulator {
 
     }
 
-    pub fn reset() -> Self {
-        Self::new()
+    pub fn quirks(&self) -> &Quirks {
+        &self.quirks
     }
 
-    pub fn tick(&mut self) {
-        let operation = self.fetch();
+    pub fn set_quirks(&mut self, quirks: Quirks) {
+        self.quirks = quirks;
+    }
 
-        self.execute(operation);
+    // toggles a single quirk by name so front-ends can take "--quirk shift_vy=true" style options,
+    // see Quirks::set for the names
+    pub fn set_quirk(&mut self, name: &str, value: bool) -> Result<(), EmulatorError> {
+        self.quirks.set(name, value)
     }
 
-    pub fn get_display(&self) -> &[bool] {
-        &self.screen
+    pub fn set_memory_policy(&mut self, policy: MemoryPolicy) {
+        self.memory_policy = policy;
     }
 
-    pub fn keypress(&mut self, idx: usize, pressed: bool) {
-        self.keys[idx] = pressed;
+    // DrawMode::Or is for experiments only, roms rely on xor drawing to erase sprites and detect collisions
+    pub fn set_draw_mode(&mut self, mode: DrawMode) {
+        self.draw_mode = mode;
     }
 
-    pub fn load(&mut self, data: &[u8]) {
-        let start = START_ADDR as usize;
-        let end = (START_ADDR as usize) + data.len();
-        self.ram[start..end].copy_from_slice(data);
+    // renderers only see what was on screen at the last present_frame, so no half drawn sprites mid-frame
+    pub fn set_double_buffering(&mut self, enabled: bool) {
+        self.double_buffered = enabled;
+        self.front_screen = self.screen;
     }
 
-    fn execute(&mut self, operation: u16) {
-        let digit1 = (operation & 0xF000) >> 12;
-        let digit2 = (operation & 0x0F00) >> 8;
-        let digit3 = (operation & 0x00F0) >> 4;
-        let digit4 = operation & 0x000F;
+    // copies everything drawn so far to the front buffer, call once per frame when double buffered
+    pub fn present_frame(&mut self) {
+        self.front_screen = self.screen;
+    }
 
-        // match opcodes
-        match (digit1, digit2, digit3, digit4) {
-            (0, 0, 0, 0) => return,
-            // 00E0 => clear display
-            (0, 0, 0xE, 0) => {
-                self.screen = [false; SCREEN_WIDTH * SCREEN_HEIGHT];
-            },
-            // 00EE => return from a subroutine
-            (0, 0, 0xE, 0xE) => {
-                // get current stack pointer
-                let subroutine_address = self.pop();
-                // get back to the current stack pointer
-                self.program_counter = subroutine_address;
-            },
-            // 1NNN => jump to an address NNN
-            (1,_,_,_) => {
-                let nnn = operation & 0xFFF;
-                self.program_counter = nnn;
-            },
-            // 2NNN => calls subroutine at NNN
-            (2,_,_,_) => {
-                let nnn = operation & 0xFFF;
-                // return to current step after
-                self.push(self.program_counter);
-                // go to address
-                self.program_counter = nnn
-            },
-            // 3XNN => skip next operation if vX == NN
-            (3,_,_,_) => {
-                let x: usize = digit2 as usize;
-                let nn = (operation & 0xFF) as u8;
+    // chip8 doesn't require aligned opcodes, but an odd pc usually means a bad BNNN or jump
+    pub fn set_strict_alignment(&mut self, strict: bool) {
+        self.strict_alignment = strict;
+    }
 
-                if self.v_registers[x] == nn {
-                    // skip 1 operation
-                    self.program_counter += 2;
-                }
-            },
-            // 4XNN => skip next operation if vX != NN
-            (4,_,_,_) => {
-                let x = digit2 as usize;
-                let nn = (operation & 0xFF) as u8;
-                if self.v_registers[x] != nn {
-                    // skip 1 operation
-                    self.program_counter += 2;
-                }
-            },
-            // 5XY0 => skip next operation if vX == vY
-            (5,_,_,0) => {
-                let x = digit2 as usize;
-                let y = digit3 as usize;
+    // one switch for "fail loud" or "best effort". strict turns on strict alignment, MemoryPolicy::Error and errors for
+    // unknown opcodes and stack over/underflow. lax wraps memory, reads odd pcs, skips unknown opcodes and ignores
+    // calls and returns the stack can't handle. set_strict_alignment and set_memory_policy still work afterwards
+    pub fn set_strict(&mut self, strict: bool) {
+        self.strict = strict;
+        self.strict_alignment = strict;
+        self.memory_policy = if strict { MemoryPolicy::Error } else { MemoryPolicy::Wrap };
+    }
 
-                if self.v_registers[x] == self.v_registers[y] {
-                    // skip next operation
-                    self.program_counter += 2;
-                }
-            },
-            // 6XNN => set vX to NN
-            (6,_,_,_) => {
-                let x = digit2 as usize;
-                let nn = (operation & 0xFF) as u8;
+    // moves the entry point, e.g. 0x600 for ETI-660 roms, kept across resets. the font lives below it,
+    // so anything under FONTSET_SIZE is refused rather than letting roms and load_clean overwrite the glyphs
+    pub fn set_start_addr(&mut self, addr: u16) -> Result<(), EmulatorError> {
+        if (addr as usize) < FONTSET_SIZE {
+            return Err(EmulatorError::StartAddressInFont { addr });
+        }
+        if addr & 1 != 0 {
+            return Err(EmulatorError::MisalignedPc { pc: addr });
+        }
+        if addr as usize + 1 >= RAM_SIZE {
+            return Err(EmulatorError::PcOutOfBounds { pc: addr });
+        }
 
-                self.v_registers[x] = nn;
-            },
-            // 7XNN => add vX to nn
-            (7,_,_,_) => {
-                let x = digit2 as usize;
-                let nn = (operation & 0xFF) as u8;
+        self.start_addr = addr;
+        self.program_counter = addr;
 
-                // use .wrapping_add instead if .add because nn may overflow resulting in panic (crash)
-                self.v_registers[x] = self.v_registers[x].wrapping_add(nn);
-            },
-            // 8XY0 => sets vX to value of xY
-            (8,_,_,0) => {
-                let x = digit2 as usize;
-                let y = digit3 as usize;
+        Ok(())
+    }
 
-                self.v_registers[x] = self.v_registers[y];
-            },
-            // 8XY1 => sets vX to the result of vX |= vY
-            (8,_,_,1) => {
-                let x = digit2 as usize;
-                let y = digit3 as usize;
+    // sets up the starting conditions a profile promises, before or after the rom is loaded.
+    // the font and anything from the start address up are never touched
+    pub fn prepare_for_rom(&mut self, compat: CompatProfile) {
+        match compat {
+            CompatProfile::Vip => self.ram[FONTSET_SIZE..self.start_addr as usize].fill(0),
+            CompatProfile::KeepLowRam => {},
+        }
 
-                self.v_registers[x] |= self.v_registers[y];
-            },
-            // 8XY2 => sets vX to the result of vX &= vY
-            (8,_,_,2) => {
-                let x = digit2 as usize;
-                let y = digit3 as usize;
+        // a rom might have overwritten the glyphs of a previous run
+        self.set_fontset_bytes(self.fontset);
+        self.v_registers = [0; NUM_REGISTERS];
+        self.i_register = 0;
+        self.stack = [0; STACK_SIZE];
+        self.stack_pointer = 0;
+        self.delay_timer = 0;
+        self.sound_timer = 0;
+        self.program_counter = self.start_addr;
+    }
 
-                self.v_registers[x] &= self.v_registers[y];
-            },
-            // 8XY3 => sets vX to the result of vX ^= vY
-            (8,_,_,3) => {
-                let x = digit2 as usize;
-                let y = digit3 as usize;
+    // same as new but CXNN draws from a seeded rng, so runs are repeatable
+    pub fn with_seed(seed: u64) -> Self {
+        let mut new_emulator = Self::new();
+        new_emulator.seed = Some(seed);
+        new_emulator.rng = Some(Pcg32::seed_from_u64(seed));
 
-                self.v_registers[x] ^= self.v_registers[y];
-            },
-            // 8XY4 => Adds vY to vX, sets vF
-            (8,_,_,4) => {
-                let x = digit2 as usize;
-                let y = digit3 as usize;
+        new_emulator
+    }
 
-                let (new_x,overflowed) = self.v_registers[x].overflowing_add(self.v_registers[y]);
-                self.v_registers[x] = new_x;
+    // CXNN draws from rng instead of the seeded or thread rng, e.g. a recorded stream or a counter.
+    // it is kept across resets as is, and step_back_frame can't rewind it
+    pub fn set_rng(&mut self, rng: Box<dyn RngCore + Send>) {
+        self.user_rng = Some(rng);
+    }
 
-                if overflowed {
-                    self.v_registers[0xF] = 1
-                } else {
-                    self.v_registers[0xF] = 0
-                }
-            },
-             // 8XY5 => subtracts vY from vX, sets vF
-            (8,_,_,5) => {
-                let x = digit2 as usize;
-                let y = digit3 as usize;
+    // puts the emulator back to its power on state, keeping the configured quirks
+    pub fn reset(&mut self) {
+        let quirks = self.quirks;
+        let memory_policy = self.memory_policy;
+        let draw_mode = self.draw_mode;
+        let strict_alignment = self.strict_alignment;
+        let strict = self.strict;
+        let sound_enabled = self.sound_enabled;
+        let double_buffered = self.double_buffered;
+        let frame_rewind = self.frame_rewind;
+        let detect_smc = self.detect_smc;
+        let start_addr = self.start_addr;
+        let timer_frequency = self.timer_frequency;
+        let instruction_budget = self.instruction_budget;
+        let fetch_endianness = self.fetch_endianness;
+        let seed = self.seed;
+        let user_rng = self.user_rng.take();
+        let watched_addrs = std::mem::take(&mut self.watched_addrs);
+        let cheats = std::mem::take(&mut self.cheats);
+        let rom_slots = std::mem::take(&mut self.rom_slots);
+        let rpl_flags = self.rpl_flags;
+        let fontset = self.fontset;
+        #[cfg(feature = "std")]
+        let crash_dump_path = self.crash_dump_path.take();
+        #[cfg(feature = "std")]
+        let max_ips = self.max_ips;
 
-                let (nex_x,overflowed) = self.v_registers[x].overflowing_sub(self.v_registers[y]);
-                self.v_registers[x] = nex_x;
+        // keys and their press order are not carried over on purpose, a key held while the old rom sat
+        // in FX0A must not resolve the next rom's first FX0A
+        *self = Self::new();
+        self.watched_addrs = watched_addrs;
+        self.cheats = cheats;
+        self.rom_slots = rom_slots;
+        self.rpl_flags = rpl_flags;
+        self.set_fontset_bytes(fontset);
+        self.timer_frequency = timer_frequency;
+        self.instruction_budget = instruction_budget;
+        self.fetch_endianness = fetch_endianness;
+        // reseed so a reset replays the same random numbers
+        self.seed = seed;
+        self.rng = seed.map(Pcg32::seed_from_u64);
+        self.user_rng = user_rng;
+        self.quirks = quirks;
+        self.memory_policy = memory_policy;
+        self.draw_mode = draw_mode;
+        self.strict_alignment = strict_alignment;
+        self.strict = strict;
+        self.sound_enabled = sound_enabled;
+        self.double_buffered = double_buffered;
+        self.frame_rewind = frame_rewind;
+        self.detect_smc = detect_smc;
+        self.start_addr = start_addr;
+        #[cfg(feature = "std")]
+        {
+            self.crash_dump_path = crash_dump_path;
+            self.max_ips = max_ips;
+        }
+        self.program_counter = start_addr;
+    }
 
-                if overflowed {
-                    self.v_registers[0xF] = 0;
-                } else {
-                    self.v_registers[0xF] = 1;
-                }
-            },
-             // 8XY6 => shifts vX one bit to the right, and sets vF
-            (8,_,_,6) => {
-                let x = digit2 as usize;
-                let least_significant_bit = self.v_registers[x] & 1;
+    // replaces the built in 0 to F glyphs, 16 glyphs of 5 bytes each, kept across resets
+    pub fn set_fontset(&mut self, font: &[u8]) -> Result<(), EmulatorError> {
+        if font.len() != FONTSET_SIZE {
+            return Err(EmulatorError::InvalidFontset { len: font.len(), expected: FONTSET_SIZE });
+        }
 
-                self.v_registers[x] >>= 1;
-                self.v_registers[0xF] = least_significant_bit;
-            },
-            // 8XY7 => subtracts vX from vY, sets vF
-            (8,_,_,7) => {
-                let x = digit2 as usize;
-                let y = digit3 as usize;
+        let mut fontset = [0; FONTSET_SIZE];
+        fontset.copy_from_slice(font);
+        self.set_fontset_bytes(fontset);
 
-                let (nex_x,overflowed) = self.v_registers[y].overflowing_sub(self.v_registers[x]);
-                self.v_registers[x] = nex_x;
+        Ok(())
+    }
 
-                if overflowed {
-                    self.v_registers[0xF] = 0;
-                } else {
-                    self.v_registers[0xF] = 1;
-                }
-            },
-            // 8XYE => shifts vX one bit to the left, and sets vF
-            (8,_,_,0xE) => {
-                let x = digit2 as usize;
-                let most_significat_bit = (self.v_registers[x] >> 7) & 1;
+    fn set_fontset_bytes(&mut self, fontset: [u8; FONTSET_SIZE]) {
+        self.fontset = fontset;
+        self.ram[..FONTSET_SIZE].copy_from_slice(&fontset);
+    }
 
-                self.v_registers[x] <<= 1;
-                self.v_registers[0xF] = most_significat_bit;
-            },
-            // 9XY0 => skip next option if vX != vY
-            (9,_,_,0) => {
-                let x = digit2 as usize;
-                let y = digit3 as usize;
+    // non-standard, lets fetch read opcodes stored low byte first
+    pub fn set_fetch_endianness(&mut self, endianness: Endianness) {
+        self.fetch_endianness = endianness;
+    }
 
-                if self.v_registers[x] != self.v_registers[y] {
-                    self.program_counter += 2;
-                }
-            },
-            // ANNN => sets i to nnn
-            (0xA,_,_,_) => {
-                let nnn = operation & 0xFFF;
-                self.i_register = nnn;
-            },
-            // BNNN => jump to the address of nnn + v[0]
-            (0xB,_,_,_) => {
-                let nnn: u16 = operation & 0xFFF;
-                self.program_counter = nnn + (self.v_registers[0] as u16);
-            },
-            // CXNN => set vx to a random value masked (bitwise AND) with NN
-            (0xC,_,_,_) => {
-                let x = digit2 as usize;
-                let nn = operation & 0xFF;
-                let random_number:u8 = rand::thread_rng().gen();
+    // rate for update_timers_elapsed, 60hz unless experimenting with odd hardware. past 1GHz the period
+    // rounds down to 0ns and update_timers_elapsed would never catch up, so those are refused like 0hz
+    pub fn set_timer_frequency(&mut self, hz: u32) -> Result<(), EmulatorError> {
+        if hz == 0 || Duration::from_secs(1) / hz == Duration::ZERO {
+            return Err(EmulatorError::InvalidTimerFrequency { hz });
+        }
 
-                self.v_registers[x] = random_number & (nn as u8);
-            },
-            // DXYN => Draws a sprite at coordinate (VX, VY) that has a width of 8 pixels and a height of N pixels. Each row of 8 pixels is read as bit-coded starting from memory location I; I value does not change after the execution of this instruction. As described above, VF is set to 1 if any screen pixels are flipped from set to unset when the sprite is drawn, and to 0 if that does not happen
-            (0xD,_,_,_) => {
-                // get cords
-                let x_cord = self.v_registers[digit2 as usize] as u16;
-                let y_cord = self.v_registers[digit3 as usize] as u16;
+        self.timer_frequency = hz;
 
-                // number of rows is the last digit
-                let rows = digit4;
-                // track the flipped flag
-                let mut flipped = false;
+        Ok(())
+    }
 
-                for row in 0..rows {
-                    let row_address = self.i_register + row as u16;
-                    let pixels = self.ram[row_address as usize];
-
-                    // 0..8 because each sprite width is 8px
-                    for col in 0..8 {
-                        // get current pixel's bit, flip if its a 1, do nothing if its a 0
-                        // cant exactly understand how the logic below works (hard copy)
-                        let is_flipped = pixels & (0b1000_0000 >> col) != 0;
-                        if is_flipped {
-                            // redraw
-                            let x = (x_cord + col) as usize % SCREEN_WIDTH;
-                            let y = (y_cord + row) as usize % SCREEN_HEIGHT;
-
-                            // Get our pixel's index for our 1D screen array
-                            let idx = x + SCREEN_WIDTH * y;
-                            // Check if we're about to flip the pixel and set
-                            flipped |= self.screen[idx];
-                            self.screen[idx] ^= true;
-                        }
-                    }
-                }
+    // resets in place then loads the new rom, used when switching roms without losing configuration
+    pub fn load_and_reset(&mut self, data: &[u8]) -> Result<(), EmulatorError> {
+        self.check_rom_size(data.len())?;
 
-                if flipped {
-                    self.v_registers[0xF] = 1;
-                } else {
-                    self.v_registers[0xF] = 0;
-                }
+        self.reset();
+        self.load(data)
+    }
 
-            },
-            // EX9E => skip on key press
-            (0xE,_,9,0xE) => {
-                let x = digit2 as usize;
-                let key_pressed = self.keys[self.v_registers[x] as usize];
+    // keeps a copy of a rom in slot so activate_slot can switch to it later, e.g. for A/B testing variants
+    pub fn load_slot(&mut self, slot: usize, data: &[u8]) -> Result<(), EmulatorError> {
+        self.check_rom_size(data.len())?;
+        if slot >= MAX_ROM_SLOTS {
+            return Err(EmulatorError::RomSlotOutOfRange { slot, max: MAX_ROM_SLOTS });
+        }
 
-                if key_pressed {
-                    self.program_counter += 2;
-                }
-            },
-            // EXA1 => skip if key is not pressed
-            (0xE,_,0xA,1) => {
-                let x = digit2 as usize;
-                let key_pressed = self.keys[self.v_registers[x] as usize];
+        if slot >= self.rom_slots.len() {
+            self.rom_slots.resize(slot + 1, Vec::new());
+        }
+        self.rom_slots[slot] = data.to_vec();
 
-                if !key_pressed {
-                    self.program_counter += 2;
-                }
-            },
-            // FX07 => sets delay timer
-            (0xF,_,0,7) => {
-                let x = digit2 as usize;
-                self.v_registers[x] = self.delay_timer;
-            },
-            // FX0A => wait for key press
-            (0xF,_,0,0xA) => {
-                let x = digit2 as usize;
-                let mut key_pressed = false;
+        Ok(())
+    }
 
-                for i in 0..self.keys.len() {
-                    if self.keys[i] {
-                        self.v_registers[x] = i as u8;
-                        key_pressed = true;
-                        break;
-                    }
-                }
+    // resets and starts the rom stored in slot
+    pub fn activate_slot(&mut self, slot: usize) -> Result<(), EmulatorError> {
+        let data = match self.rom_slots.get(slot) {
+            Some(data) if !data.is_empty() => data.clone(),
+            _ => return Err(EmulatorError::EmptyRomSlot { slot }),
+        };
 
-                // using this flag because in a loop our code would not be able to process a key press, turning into infinity loop (from guide)
-                if !key_pressed {
-                    // retry opcode
-                    self.program_counter -= 2;
-                }
-            },
-            // FX15 => set delay timer to vX
-            (0xF,_,1,5) => {
-                let x = digit2 as usize;
-                self.delay_timer = self.v_registers[x];
-            },
-            // FX18 => set sound timer to vX
-            (0xF,_,1,8) => {
-                let x = digit2 as usize;
-                self.sound_timer = self.v_registers[x];
-            },
-            // FX1E => adds vX to I
-            (0xF,_,1,0xE) => {
-                let x = digit2 as usize;
-                self.i_register = self.i_register.wrapping_add(self.v_registers[x] as u16);
-            },
-            // FX29 => sets I to font address
-            (0xF,_,2,9) => {
-                let x = digit2 as usize;
-                let character_position = self.v_registers[x] as u16;
+        self.load_and_reset(&data)
+    }
 
-                // times 5 because each font is 5 bytes each
-                self.i_register = character_position * 5;
-            },
-            // FX33 => Stores the binary-coded decimal representation of VX, with the hundreds digit in memory at location in I, the tens digit at location I+1, and the ones digit at location I+2
-            (0xF,_,3,3) => {
-                let x = digit2 as usize;
-                let v_x = self.v_registers[x] as f32;
+    pub fn tick(&mut self) -> Result<(), EmulatorError> {
+        let result = self.step();
 
-                let hundreds = (v_x / 100.0).floor();
-                let tens = ((v_x / 10.0) % 10.0).floor();
-                let ones = (v_x % 10.0).floor();
+        #[cfg(feature = "std")]
+        if let Err(err) = &result {
+            self.write_crash_dump(err);
+        }
 
-                self.ram[self.i_register as usize] = hundreds as u8;
-                self.ram[(self.i_register + 1) as usize] = tens as u8;
-                self.ram[(self.i_register + 2) as usize] = ones as u8;
-            },
-            // FX55 => Stores from V0 to VX (including VX) in memory, starting at address I. The offset from I is increased by 1 for each value written, but I itself is left unmodified
-            (0xF,_,5,5) => {
-                let x = digit2 as usize;
-                let offset = self.i_register as usize;
+        result
+    }
 
-                // ..= (including vX)
-                for i in 0..=x {
-                    // store in memory (ram)
-                    self.ram[offset + i] = self.v_registers[i];
-                }
-            },
-            // FX65 => Fills from V0 to VX (including VX) with values from memory, starting at address I. The offset from I is increased by 1 for each value read, but I itself is left unmodified
-            (0xF,_,6,5) => {
-                let x = digit2 as usize;
-                let offset = self.i_register as usize;
+    // tick that also reports what the instruction changed, slower since it compares ram before and after
+    pub fn single_cycle(&mut self) -> Result<CycleReport, EmulatorError> {
+        let pc = self.program_counter;
+        let opcode = self.opcode_at(pc).ok_or(EmulatorError::PcOutOfBounds { pc })?;
+        let registers = self.v_registers;
+        let i_register = self.i_register;
+        let ram = self.ram;
+        let screen = self.screen;
+        let plane2 = self.plane2;
 
-                // ..= (including vX)
-                for i in 0..=x {
-                    // store in memory (ram)
-                    self.v_registers[i] = self.ram[offset + i];
-                }
-            },
-            (_, _, _, _) => unimplemented!("Unimplemented opcode: {}", operation),
-        }
+        self.tick()?;
+
+        Ok(CycleReport {
+            pc,
+            opcode,
+            instruction: decode(opcode),
+            changed_registers: (0..NUM_REGISTERS).filter(|&idx| registers[idx] != self.v_registers[idx]).collect(),
+            changed_memory: (0..RAM_SIZE).filter(|&address| ram[address] != self.ram[address]).map(|address| address as u16).collect(),
+            i_changed: i_register != self.i_register,
+            screen_changed: screen != self.screen || plane2 != self.plane2,
+        })
     }
 
-    pub fn update_timers(&mut self) {
-        if self.delay_timer > 0 {
-            self.delay_timer -=1;
-        }
+    // fetch and execute one instruction, tick wraps this to report crashes
+    fn step(&mut self) -> Result<(), EmulatorError> {
+        let pc = self.program_counter;
+        let operation = self.fetch()?;
+        self.cycle_count += 1;
+        self.instructions_this_frame += 1;
+        self.last_tick_drew = false;
+        self.skipped_addr = None;
 
-        if self.sound_timer > 0 {
-            if self.sound_timer == 1 {
-                self.beep();
+        trace!("{:#06X}: {:04X} {}", pc, operation, disassembler::disassemble(operation));
+        if self.crash_dump_enabled() {
+            if self.recent_trace.len() == CRASH_TRACE_LENGTH {
+                self.recent_trace.pop_front();
             }
-            self.sound_timer -=1;
+            self.recent_trace.push_back((pc, operation));
+        }
+
+        let result = self.execute(operation);
+
+        // cheats win over whatever the rom just wrote
+        for &(address, value) in self.cheats.iter() {
+            self.ram[address as usize] = value;
         }
 
+        result
     }
 
-    fn beep(&mut self) {
-        let (_stream, handle) = rodio::OutputStream::try_default().unwrap();
-        let sink = rodio::Sink::try_new(&handle).unwrap();
+    // human readable snapshot of the cpu, one "name: value" group per line:
+    //   pc: 0x0204  i: 0x0300  sp: 1
+    //   delay: 0  sound: 0  cycles: 12
+    //   v0: 0x00 v1: 0x00 ... vF: 0x01
+    //   stack: 0x0202 (outermost first, "stack: empty" when there are no calls)
+    pub fn dump_state(&self) -> String {
+        let mut dump = String::new();
 
-        let current_dir =  std::env::current_dir().unwrap();
-        let sound_relative_path = String::from("chip8_core/sounds/beep.wav");
-        let sound_absoulte_path = format!("{}/{sound_relative_path}",current_dir.display());
+        let _ = writeln!(dump, "pc: {:#06X}  i: {:#06X}  sp: {}", self.program_counter, self.i_register, self.stack_pointer);
+        let _ = writeln!(dump, "delay: {}  sound: {}  cycles: {}", self.delay_timer, self.sound_timer, self.cycle_count);
 
-        let file = match std::fs::File::open(sound_absoulte_path.to_string()) {
-            Ok(file) => file,
-            Err(_) => {
-                println!("Unexpected Error opening the sound file");
-                return;
-            }
-        };
+        let registers: Vec<String> = self.v_registers.iter().enumerate()
+            .map(|(idx, value)| format!("v{:X}: {:#04X}", idx, value))
+            .collect();
+        let _ = writeln!(dump, "{}", registers.join(" "));
 
-        sink.append(rodio::Decoder::new(BufReader::new(file)).unwrap());
-        sink.set_speed(8.0);
-        sink.sleep_until_end();
+        if self.call_stack().is_empty() {
+            let _ = writeln!(dump, "stack: empty");
+        } else {
+            let frames: Vec<String> = self.call_stack().iter().map(|address| format!("{:#06X}", address)).collect();
+            let _ = writeln!(dump, "stack: {}", frames.join(" "));
+        }
+
+        dump
     }
 
-    fn fetch(&mut self) -> u16 {
-        // get current operation take 2 because each ram item is 8 bytes
-        let higher_byte = self.ram[self.program_counter as usize] as u16;
-        let lower_byte = self.ram[(self.program_counter + 1) as usize] as u16;
+    // on the next error from tick, writes a crash report to path:
+    //   "chip8 crash report", the error, dump_state(), then the last instructions run (oldest first)
+    //   as "0x0200: 00E0 CLS" lines
+    #[cfg(feature = "std")]
+    pub fn set_crash_dump_path(&mut self, path: impl Into<PathBuf>) {
+        self.crash_dump_path = Some(path.into());
+    }
 
-        // update program position
-        self.program_counter += 2;
+    #[cfg(feature = "std")]
+    fn crash_dump_enabled(&self) -> bool {
+        self.crash_dump_path.is_some()
+    }
+
+    #[cfg(not(feature = "std"))]
+    fn crash_dump_enabled(&self) -> bool {
+        false
+    }
 
-        let operation = (higher_byte << 8) | lower_byte; // Combines the bytes into one
+    #[cfg(feature = "std")]
+    fn write_crash_dump(&self, err: &EmulatorError) {
+        let path = match &self.crash_dump_path {
+            Some(path) => path,
+            None => return,
+        };
 
-        // return operation
-        operation
+        let mut report = String::from("chip8 crash report\n");
+        let _ = writeln!(report, "error: {}", err);
+        report.push_str(&self.dump_state());
+        let _ = writeln!(report, "last {} instructions:", self.recent_trace.len());
+        for (pc, operation) in self.recent_trace.iter() {
+            let _ = writeln!(report, "{:#06X}: {:04X} {}", pc, operation, disassembler::disassemble(*operation));
+        }
 
+        if let Err(err) = std::fs::write(path, report) {
+            warn!("couldn't write crash dump to {}: {}", path.display(), err);
+        }
     }
 
-    fn push(&mut self, val: u16) {
-        self.stack[self.stack_pointer as usize] = val;
-        self.stack_pointer += 1;
+    pub fn pc(&self) -> u16 {
+        self.program_counter
     }
 
-    fn pop(&mut self) -> u16 {
-        self.stack_pointer -= 1;
-        self.stack[self.stack_pointer as usize]
+    // copy of v0..vF, cheap enough to take every step and diff in a debugger
+    pub fn v_register(&self, idx: usize) -> Option<u8> {
+        self.v_registers.get(idx).copied()
+    }
+
+    // for setting up test preconditions or editing registers from a debugger
+    pub fn set_v_register(&mut self, idx: usize, val: u8) -> Result<(), EmulatorError> {
+        match self.v_registers.get_mut(idx) {
+            Some(register) => {
+                *register = val;
+                Ok(())
+            },
+            None => Err(EmulatorError::InvalidRegisterIndex { index: idx }),
+        }
+    }
+
+    pub fn i_register(&self) -> u16 {
+        self.i_register
+    }
+
+    // masked to 12 bits like ANNN, there is no extended memory mode that could use the rest
+    pub fn set_i_register(&mut self, val: u16) {
+        self.i_register = val & 0xFFF;
+    }
+
+    pub fn registers_snapshot(&self) -> [u8; NUM_REGISTERS] {
+        self.v_registers
+    }
+
+    pub fn restore_registers(&mut self, registers: [u8; NUM_REGISTERS]) {
+        self.v_registers = registers;
+    }
+
+    // return addresses of the active 2NNN calls, outermost first
+    pub fn call_stack(&self) -> &[u16] {
+        &self.stack[..self.stack_pointer as usize]
+    }
+
+    // every stack slot, including stale return addresses above the stack pointer
+    pub fn stack_raw(&self) -> &[u16; STACK_SIZE] {
+        &self.stack
+    }
+
+    // number of slots in use, the next 2NNN writes to stack_raw()[stack_pointer]
+    pub fn stack_pointer(&self) -> u16 {
+        self.stack_pointer
+    }
+
+    // "step out" for debuggers, pops up to frames calls and continues from the outermost one's return address
+    pub fn unwind(&mut self, frames: usize) {
+        for _ in 0..frames {
+            match self.pop() {
+                Ok(address) => self.program_counter = address,
+                Err(_) => break,
+            }
+        }
+    }
+
+    pub fn cycle_count(&self) -> u64 {
+        self.cycle_count
+    }
+
+    // sprites drawn since power on, against cycle_count this is how draw heavy a rom is
+    pub fn draw_calls(&self) -> u64 {
+        self.draw_calls
+    }
+
+    // true when the last tick was a 00E0 or DXYN that changed the display (or blank_display ran since),
+    // front-ends can skip presenting otherwise
+    pub fn last_tick_drew(&self) -> bool {
+        self.last_tick_drew
+    }
+
+    // instructions run since the timers last counted down, i.e. the cpu/timer ratio for the current frame
+    pub fn elapsed_instructions_this_frame(&self) -> u32 {
+        self.instructions_this_frame
+    }
+
+    // reports every write the rom makes to addr through take_watch_hits, handy for self modifying code
+    pub fn watch_addr(&mut self, addr: u16) {
+        if !self.watched_addrs.contains(&addr) {
+            self.watched_addrs.push(addr);
+        }
+    }
+
+    // pins a ram address to val, e.g. to freeze a lives counter, addresses outside ram are ignored
+    pub fn add_cheat(&mut self, addr: u16, val: u8) {
+        if addr as usize >= RAM_SIZE {
+            return;
+        }
+
+        self.cheats.retain(|&(address, _)| address != addr);
+        self.cheats.push((addr, val));
+        self.ram[addr as usize] = val;
+    }
+
+    pub fn clear_cheats(&mut self) {
+        self.cheats.clear();
+    }
+
+    // returns (cycle, address, new value) for each watched write since the last call
+    pub fn take_watch_hits(&mut self) -> Vec<(u64, u16, u8)> {
+        std::mem::take(&mut self.watch_hits)
+    }
+
+    // flags roms executing bytes they stored with FX33/FX55, only writes made while this is on count
+    pub fn set_detect_smc(&mut self, on: bool) {
+        self.detect_smc = on;
+        if !on {
+            self.rom_written = [0; RAM_SIZE / 64];
+        }
+    }
+
+    // addresses an opcode was fetched from after the rom wrote to it, since the last call
+    pub fn take_smc_events(&mut self) -> Vec<u16> {
+        std::mem::take(&mut self.smc_events)
+    }
+
+    pub fn get_display(&self) -> &[bool] {
+        self.displayed_screen()
+    }
+
+    // XO-CHIP's second plane, blank unless a rom selected it with FN01. get_display only shows the first
+    pub fn plane2(&self) -> &[bool] {
+        &self.plane2
+    }
+
+    // planes the next 00E0 and DXYN work on, 0b01 (the first plane only) until a rom runs FN01
+    pub fn plane_mask(&self) -> u8 {
+        self.plane_mask
+    }
+
+    // expands the display into RGBA8 bytes (4 per pixel), ready to upload as a texture
+    pub fn frame_buffer_rgba(&self, fg: [u8; 4], bg: [u8; 4]) -> Vec<u8> {
+        self.frame_buffer_rgba_palette([bg, fg, fg, fg])
+    }
+
+    // same as frame_buffer_rgba but colours each pixel by its plane value (0 to 3, XO-CHIP style),
+    // bit 0 is the displayed screen and bit 1 is plane2, which is always live since it isn't double buffered
+    pub fn frame_buffer_rgba_palette(&self, palette: [[u8; 4]; 4]) -> Vec<u8> {
+        let mut buffer = Vec::with_capacity(SCREEN_WIDTH * SCREEN_HEIGHT * 4);
+
+        for (pixel_is_on, plane2_is_on) in self.displayed_screen().iter().zip(self.plane2.iter()) {
+            buffer.extend_from_slice(&palette[*pixel_is_on as usize | (*plane2_is_on as usize) << 1]);
+        }
+
+        buffer
+    }
+
+    // the reverse of display_to_ascii, 32 lines of 64 '#' or '.' replace the whole screen (both buffers),
+    // nothing changes if the art is the wrong size or has other characters in it
+    pub fn set_screen_from_ascii(&mut self, art: &str) -> Result<(), EmulatorError> {
+        let mut screen = [false; SCREEN_WIDTH * SCREEN_HEIGHT];
+        let mut rows = 0;
+
+        for (y, line) in art.lines().enumerate() {
+            let line = line.trim_end_matches('\r');
+            if y >= SCREEN_HEIGHT || line.len() != SCREEN_WIDTH {
+                return Err(EmulatorError::InvalidAsciiArt { row: y });
+            }
+
+            for (x, character) in line.chars().enumerate() {
+                screen[x + SCREEN_WIDTH * y] = match character {
+                    '#' => true,
+                    '.' => false,
+                    _ => return Err(EmulatorError::InvalidAsciiArt { row: y }),
+                };
+            }
+            rows += 1;
+        }
+
+        if rows != SCREEN_HEIGHT {
+            return Err(EmulatorError::InvalidAsciiArt { row: rows });
+        }
+
+        self.screen = screen;
+        self.front_screen = screen;
+
+        Ok(())
+    }
+
+    // the display for streaming to a viewer, laid out as
+    //   version, width, height, plane count (one byte each), then every plane row by row, 8 pixels a byte, msb first
+    // this core is always 64x32 and only the first plane is sent, the header leaves room for 128x64 and plane2
+    pub fn encode_frame(&self) -> Vec<u8> {
+        let mut frame = vec![FRAME_VERSION, SCREEN_WIDTH as u8, SCREEN_HEIGHT as u8, 1];
+
+        frame.extend(self.displayed_screen().chunks(8).map(|pixels| {
+            pixels.iter().fold(0, |byte, &pixel_is_on| byte << 1 | pixel_is_on as u8)
+        }));
+
+        frame
+    }
+
+    // shows a frame from encode_frame (both buffers), nothing changes if the frame has a different version,
+    // size or plane count than this core can show
+    pub fn decode_frame(&mut self, bytes: &[u8]) -> Result<(), EmulatorError> {
+        if bytes.len() < FRAME_HEADER_SIZE {
+            return Err(EmulatorError::InvalidFrame { reason: "too short for the header" });
+        }
+        if bytes[0] != FRAME_VERSION {
+            return Err(EmulatorError::InvalidFrame { reason: "unknown version" });
+        }
+        if (bytes[1] as usize, bytes[2] as usize, bytes[3]) != (SCREEN_WIDTH, SCREEN_HEIGHT, 1) {
+            return Err(EmulatorError::InvalidFrame { reason: "only 64x32 frames with one plane are supported" });
+        }
+        let pixels = &bytes[FRAME_HEADER_SIZE..];
+        if pixels.len() != SCREEN_WIDTH * SCREEN_HEIGHT / 8 {
+            return Err(EmulatorError::InvalidFrame { reason: "pixel data doesn't match the header" });
+        }
+
+        let mut screen = [false; SCREEN_WIDTH * SCREEN_HEIGHT];
+        for (idx, pixel) in screen.iter_mut().enumerate() {
+            *pixel = pixels[idx / 8] & (0x80 >> (idx % 8)) != 0;
+        }
+        self.screen = screen;
+        self.front_screen = screen;
+
+        Ok(())
+    }
+
+    // clears both screen buffers and plane2 for a front-end (on pause or a rom switch), unlike 00E0 nothing
+    // else changes. last_tick_drew is set so loops that only present after a draw pick the blank screen up
+    pub fn blank_display(&mut self) {
+        self.screen = [false; SCREEN_WIDTH * SCREEN_HEIGHT];
+        self.front_screen = self.screen;
+        self.plane2 = self.screen;
+        self.last_tick_drew = true;
+    }
+
+    // number of pixels that are on, a cheap check before a full screen_diff
+    pub fn lit_pixel_count(&self) -> usize {
+        self.displayed_screen().iter().filter(|pixel_is_on| **pixel_is_on).count()
+    }
+
+    // (min_x, min_y, max_x, max_y) of the lit pixels, inclusive, None when the screen is blank
+    pub fn lit_bounds(&self) -> Option<(usize, usize, usize, usize)> {
+        let mut bounds: Option<(usize, usize, usize, usize)> = None;
+
+        for (idx, pixel_is_on) in self.displayed_screen().iter().enumerate() {
+            if !*pixel_is_on {
+                continue;
+            }
+
+            let (x, y) = (idx % SCREEN_WIDTH, idx / SCREEN_WIDTH);
+            bounds = Some(match bounds {
+                Some((min_x, min_y, max_x, max_y)) => (min_x.min(x), min_y.min(y), max_x.max(x), max_y.max(y)),
+                None => (x, y, x, y),
+            });
+        }
+
+        bounds
+    }
+
+    // rows of pixels so display_2d()[y][x] is the pixel at (x, y), easier than get_display's flat array but copies
+    pub fn display_2d(&self) -> Vec<Vec<bool>> {
+        self.displayed_screen().chunks(SCREEN_WIDTH).map(|row| row.to_vec()).collect()
+    }
+
+    // '#' for on and '.' for off, one line per row, handy for logs and eyeballing test failures
+    pub fn display_to_ascii(&self) -> String {
+        let mut ascii = String::with_capacity((SCREEN_WIDTH + 1) * SCREEN_HEIGHT);
+
+        for row in self.displayed_screen().chunks(SCREEN_WIDTH) {
+            for pixel_is_on in row {
+                ascii.push(if *pixel_is_on { '#' } else { '.' });
+            }
+            ascii.push('\n');
+        }
+
+        ascii
+    }
+
+    // plain (P1) pbm image of the screen, lit pixels are 1 (black). rows are written without
+    // separators so they stay under the 70 character line limit
+    pub fn display_to_pbm(&self) -> String {
+        let mut pbm = format!("P1\n{} {}\n", SCREEN_WIDTH, SCREEN_HEIGHT);
+
+        for row in self.displayed_screen().chunks(SCREEN_WIDTH) {
+            for pixel_is_on in row {
+                pbm.push(if *pixel_is_on { '1' } else { '0' });
+            }
+            pbm.push('\n');
+        }
+
+        pbm
+    }
+
+    // reads a single pixel, anything off screen counts as off
+    pub fn get_pixel(&self, x: usize, y: usize) -> bool {
+        if x >= SCREEN_WIDTH || y >= SCREEN_HEIGHT {
+            return false;
+        }
+
+        self.displayed_screen()[x + SCREEN_WIDTH * y]
+    }
+
+    // sets a single pixel directly, coordinates off screen are ignored
+    // this is an edit rather than drawing, so it shows up straight away even when double buffered
+    pub fn set_pixel(&mut self, x: usize, y: usize, on: bool) {
+        if x >= SCREEN_WIDTH || y >= SCREEN_HEIGHT {
+            return;
+        }
+
+        self.screen[x + SCREEN_WIDTH * y] = on;
+        self.front_screen[x + SCREEN_WIDTH * y] = on;
+    }
+
+    // the buffer renderers should see
+    fn displayed_screen(&self) -> &[bool; SCREEN_WIDTH * SCREEN_HEIGHT] {
+        if self.double_buffered {
+            &self.front_screen
+        } else {
+            &self.screen
+        }
+    }
+
+    pub fn keypress(&mut self, idx: usize, pressed: bool) -> Result<(), EmulatorError> {
+        if idx >= NUM_KEYS {
+            return Err(EmulatorError::InvalidKeyIndex { index: idx });
+        }
+
+        self.keys[idx] = pressed;
+
+        if pressed {
+            self.key_press_counter += 1;
+            self.key_press_order[idx] = self.key_press_counter;
+        }
+
+        Ok(())
+    }
+
+    // releases every key, for when the front-end may have missed key up events (e.g. lost focus)
+    pub fn reset_keys(&mut self) {
+        self.keys = [false; NUM_KEYS];
+    }
+
+    // presses and releases keys to match a keys_mask style bitmask
+    pub fn set_keys(&mut self, mask: u16) {
+        for idx in 0..NUM_KEYS {
+            let pressed = mask & (1 << idx) != 0;
+            if pressed != self.keys[idx] {
+                // idx is always a valid key
+                let _ = self.keypress(idx, pressed);
+            }
+        }
+    }
+
+    // the keys the rom sees for the current frame, what input recordings are made of
+    pub fn frame_input_snapshot(&self) -> u16 {
+        self.keys_mask()
+    }
+
+    // records frame_input_snapshot at every update_timers until take_input_recording, so replays are frame
+    // accurate no matter when the host delivered the key events. reset before recording and before playing
+    pub fn start_input_recording(&mut self) {
+        self.input_recording = Some(Vec::new());
+    }
+
+    // stops recording and hands back one key mask per frame
+    pub fn take_input_recording(&mut self) -> Vec<u16> {
+        self.input_recording.take().unwrap_or_default()
+    }
+
+    // replays a recording, the first frame's keys are applied now and the rest at each update_timers
+    pub fn play_input_recording(&mut self, frames: Vec<u16>) {
+        self.input_playback = frames.into();
+        self.apply_next_input_frame();
+    }
+
+    pub fn is_playing_input(&self) -> bool {
+        !self.input_playback.is_empty()
+    }
+
+    fn apply_next_input_frame(&mut self) {
+        if let Some(mask) = self.input_playback.pop_front() {
+            self.set_keys(mask);
+        }
+    }
+
+    // pressed keys as a bitmask, bit N set while key N is down
+    pub fn keys_mask(&self) -> u16 {
+        self.keys
+            .iter()
+            .enumerate()
+            .fold(0, |mask, (idx, pressed)| if *pressed { mask | 1 << idx } else { mask })
+    }
+
+    // reads the 2 byte opcode stored at addr, None if it runs off the end of ram
+    pub fn opcode_at(&self, addr: u16) -> Option<u16> {
+        let addr = addr as usize;
+        if addr + 1 >= RAM_SIZE {
+            return None;
+        }
+
+        Some(self.combine_bytes(self.ram[addr], self.ram[addr + 1]))
+    }
+
+    // for status bars, shown next to pc without fetching again
+    pub fn last_opcode(&self) -> u16 {
+        self.last_opcode
+    }
+
+    // the opcode the last tick jumped over when it was a skip (3XNN, 4XNN, 5XY0, 9XY0, EX9E, EXA1) that skipped
+    pub fn skipped_opcode(&self) -> Option<u16> {
+        self.skipped_addr.and_then(|address| self.opcode_at(address))
+    }
+
+    // opcode and mnemonic that the next tick will run, for a debugger's "next" view
+    pub fn next_instruction(&self) -> Option<(u16, String)> {
+        self.opcode_at(self.program_counter)
+            .map(|operation| (operation, disassembler::disassemble(operation)))
+    }
+
+    // (address, mnemonic) for count opcodes from addr on, for a debugger's disassembly pane.
+    // stops early at the end of ram
+    pub fn disassemble_from(&self, addr: u16, count: usize) -> Vec<(u16, String)> {
+        (0..count)
+            .map(|i| addr as usize + i * 2)
+            .map_while(|address| {
+                let address = u16::try_from(address).ok()?;
+                self.opcode_at(address).map(|operation| (address, disassembler::disassemble(operation)))
+            })
+            .collect()
+    }
+
+    // the 5 byte glyph FX29 would point I at for digit, read from ram so a rom that rewrote the font shows up
+    pub fn font_sprite(&self, digit: u8) -> Option<[u8; 5]> {
+        if digit > 0xF {
+            return None;
+        }
+
+        let start = digit as usize * 5;
+        let mut glyph = [0; 5];
+        glyph.copy_from_slice(&self.ram[start..start + 5]);
+
+        Some(glyph)
+    }
+
+    // copies up to len bytes of ram from start, cut short instead of running past the end
+    pub fn memory_dump(&self, start: u16, len: usize) -> Vec<u8> {
+        let start = (start as usize).min(RAM_SIZE);
+        let end = start.saturating_add(len).min(RAM_SIZE);
+
+        self.ram[start..end].to_vec()
+    }
+
+    // copy of all of ram, for snapshots on disk or diffing two runs
+    pub fn ram_image(&self) -> [u8; RAM_SIZE] {
+        self.ram
+    }
+
+    // replaces all of ram with a full image from ram_image
+    pub fn set_ram_image(&mut self, image: &[u8]) -> Result<(), EmulatorError> {
+        if image.len() != RAM_SIZE {
+            return Err(EmulatorError::InvalidRamImage { len: image.len(), expected: RAM_SIZE });
+        }
+
+        self.ram.copy_from_slice(image);
+
+        Ok(())
+    }
+
+    // rough instructions per second this machine manages, from ~50ms of a throwaway emulator running an
+    // add/jump loop with the same quirks. results vary by machine and load, it's meant for warning a user
+    // that their target cpu speed can't be kept up, not for exact numbers. only the quirks are read from self,
+    // the loop runs on its own emulator so this one's ram, registers and counters are left as they were
+    pub fn estimate_ips(&self) -> u64 {
+        let mut emulator = Emulator::new();
+        emulator.set_quirks(self.quirks);
+        // 7001 => v0 += 1, 1200 => jump back
+        emulator.load(&[0x70, 0x01, 0x12, 0x00]).expect("4 bytes always fit");
+
+        let start = Instant::now();
+        let mut ticks: u64 = 0;
+        while start.elapsed() < IPS_SAMPLE_TIME {
+            for _ in 0..1000 {
+                // the loop can't fail, anything else would be a bug in the core
+                emulator.tick().expect("estimate_ips loop errored");
+            }
+            ticks += 1000;
+        }
+
+        (ticks as f64 / start.elapsed().as_secs_f64()) as u64
+    }
+
+    // caps run_budgeted_frame and run_rom_to_completion at ips instructions a second by sleeping, so a runaway
+    // budget can't keep a cpu core busy. tick() and the front-end's own loops are never slowed down.
+    // Some(0) is treated like None, uncapped, rather than never running anything
+    #[cfg(feature = "std")]
+    pub fn set_max_ips(&mut self, ips: Option<u32>) {
+        self.max_ips = ips.filter(|&ips| ips > 0);
+        self.ips_window = None;
+    }
+
+    // sleeps whenever the capped loops get ahead of max_ips
+    #[cfg(feature = "std")]
+    fn throttle(&mut self) {
+        let max_ips = match self.max_ips {
+            Some(max_ips) => max_ips,
+            None => return,
+        };
+
+        let (start, instructions) = self.ips_window.get_or_insert_with(|| (Instant::now(), 0));
+        *instructions += 1;
+
+        let expected = Duration::from_secs_f64(*instructions as f64 / max_ips as f64);
+        let elapsed = start.elapsed();
+        if elapsed > expected + Duration::from_secs(1) {
+            // the loop wasn't called for a while (paused front-end), don't let it burst to catch up
+            self.ips_window = None;
+        } else if expected > elapsed + Duration::from_millis(1) {
+            std::thread::sleep(expected - elapsed);
+        }
+    }
+
+    // batch helper for roms that compute something and then spin on a self jump, runs until is_halted or
+    // max_cycles more instructions, counting the timers down every CYCLES_PER_FRAME, returns the cycle count
+    pub fn run_rom_to_completion(&mut self, max_cycles: u64) -> Result<u64, EmulatorError> {
+        let mut cycles = 0;
+
+        while !self.is_halted() && cycles < max_cycles {
+            self.tick()?;
+            cycles += 1;
+            #[cfg(feature = "std")]
+            self.throttle();
+
+            if cycles % CYCLES_PER_FRAME == 0 {
+                self.update_timers();
+            }
+        }
+
+        Ok(self.cycle_count)
+    }
+
+    // instructions run_budgeted_frame runs per frame, below 1 is slow motion (0.5 is one instruction every other
+    // frame). negative, NaN and infinite budgets count as 0
+    pub fn set_instruction_budget(&mut self, budget: f64) {
+        self.instruction_budget = if budget.is_finite() { budget.max(0.0) } else { 0.0 };
+        self.budget_remainder = 0.0;
+    }
+
+    // runs one frame worth of the instruction budget then counts the timers down, returning how many instructions ran
+    pub fn run_budgeted_frame(&mut self) -> Result<usize, EmulatorError> {
+        self.budget_remainder += self.instruction_budget;
+        let instructions = self.budget_remainder.floor();
+        self.budget_remainder -= instructions;
+
+        for _ in 0..instructions as usize {
+            self.tick()?;
+            #[cfg(feature = "std")]
+            self.throttle();
+        }
+        self.update_timers();
+
+        Ok(instructions as usize)
+    }
+
+    // true when the next operation is a 1NNN jumping to itself, the usual way roms "end"
+    pub fn is_halted(&self) -> bool {
+        match self.opcode_at(self.program_counter) {
+            Some(operation) => operation & 0xF000 == 0x1000 && operation & 0xFFF == self.program_counter,
+            None => false,
+        }
+    }
+
+    // loads whitespace separated 4 digit hex opcodes like "00E0 A22A 600C", returning the bytes loaded
+    pub fn load_hex_string(&mut self, hex: &str) -> Result<usize, EmulatorError> {
+        let mut data = Vec::new();
+
+        for token in hex.split_whitespace() {
+            let is_opcode = token.len() == 4 && token.chars().all(|c| c.is_ascii_hexdigit());
+            if !is_opcode {
+                return Err(EmulatorError::InvalidHexToken { token: token.to_string() });
+            }
+
+            // checked above so this can't fail
+            let operation = u16::from_str_radix(token, 16).unwrap();
+            data.extend_from_slice(&operation.to_be_bytes());
+        }
+
+        self.load(&data)?;
+
+        Ok(data.len())
+    }
+
+    // decodes a standard (padded) base64 rom and loads it like load, returning how many bytes were loaded.
+    // whitespace is skipped so text wrapped over several lines works
+    #[cfg(feature = "base64")]
+    pub fn load_base64(&mut self, b64: &str) -> Result<usize, EmulatorError> {
+        use base64::Engine;
+
+        let text: String = b64.split_whitespace().collect();
+        let data = base64::engine::general_purpose::STANDARD.decode(text)?;
+        self.load(&data)?;
+
+        Ok(data.len())
+    }
+
+    // roms have to fit between the start address and the end of ram
+    fn check_rom_size(&self, size: usize) -> Result<(), EmulatorError> {
+        let max = RAM_SIZE - self.start_addr as usize;
+        if size > max {
+            return Err(EmulatorError::RomTooLarge { size, max });
+        }
+
+        Ok(())
+    }
+
+    // copies a rom in at the start address, RomTooLarge leaves ram as it was
+    pub fn load(&mut self, data: &[u8]) -> Result<(), EmulatorError> {
+        self.check_rom_size(data.len())?;
+
+        let start = self.start_addr as usize;
+        let end = (self.start_addr as usize) + data.len();
+        self.ram[start..end].copy_from_slice(data);
+        self.rom_len = data.len();
+        self.rom_checksum = fnv1a(data);
+
+        Ok(())
+    }
+
+    // like load, but zeroes everything from the start address up first so a smaller rom can't read
+    // what a previous one left behind. the font and the rest of the registers are left alone
+    pub fn load_clean(&mut self, data: &[u8]) -> Result<(), EmulatorError> {
+        self.check_rom_size(data.len())?;
+
+        self.ram[self.start_addr as usize..].fill(0);
+        self.load(data)
+    }
+
+    // reads a rom file and loads it like load, returning how many bytes were loaded
+    #[cfg(feature = "std")]
+    pub fn load_rom_path<P: AsRef<Path>>(&mut self, path: P) -> Result<usize, EmulatorError> {
+        let data = std::fs::read(path)?;
+        self.load(&data)?;
+
+        Ok(data.len())
+    }
+
+    // reads a rom file into slot like load_slot, so a playlist can be filled straight from disk
+    #[cfg(feature = "std")]
+    pub fn load_slot_path<P: AsRef<Path>>(&mut self, slot: usize, path: P) -> Result<usize, EmulatorError> {
+        let data = std::fs::read(path)?;
+        self.load_slot(slot, &data)?;
+
+        Ok(data.len())
+    }
+
+    // (address, opcode) of every 2 byte word in a rom that execute can't run, e.g. SUPER-CHIP's 00FF,
+    // before loading it. sprites and other data are decoded too, so expect some false positives.
+    // words of an oversized rom that would sit past address 0xFFFF have no address to report and are left out
+    pub fn unsupported_opcodes(&self, data: &[u8]) -> Vec<(u16, u16)> {
+        data.chunks_exact(2)
+            .enumerate()
+            .filter_map(|(i, bytes)| {
+                let address = u16::try_from(self.start_addr as usize + i * 2).ok()?;
+                let operation = self.combine_bytes(bytes[0], bytes[1]);
+                match decode(operation) {
+                    Instruction::Unknown { .. } => Some((address, operation)),
+                    _ => None,
+                }
+            })
+            .collect()
+    }
+
+    // true when a rom has any EX9E, EXA1 or FX0A, so a front-end can tell the player a demo has no controls.
+    // data is scanned as opcodes on even offsets, so a sprite byte pair that happens to decode as a key opcode
+    // counts too and code at odd addresses is missed
+    pub fn uses_input(&self, data: &[u8]) -> bool {
+        data.chunks_exact(2)
+            .any(|bytes| decode(self.combine_bytes(bytes[0], bytes[1])).class() == OpClass::Input)
+    }
+
+    // keys a rom checks with EX9E/EXA1, for controller mapping UIs. the key is whatever vX holds at runtime,
+    // so only the common "6XNN then EX9E/EXA1" pattern is recognised and anything else is left out
+    pub fn referenced_keys(&self, data: &[u8]) -> HashSet<u8> {
+        let instructions: Vec<Instruction> = data
+            .chunks_exact(2)
+            .map(|bytes| decode(self.combine_bytes(bytes[0], bytes[1])))
+            .collect();
+
+        instructions
+            .windows(2)
+            .filter_map(|pair| match pair {
+                [Instruction::Set { x: set_x, nn }, Instruction::SkipIfKey { x } | Instruction::SkipIfNotKey { x }]
+                    if set_x == x && (*nn as usize) < NUM_KEYS => Some(*nn),
+                _ => None,
+            })
+            .collect()
+    }
+
+    // loads a rom bundled with its own font, the pack is laid out as
+    //   "C8PK" magic, font length (u16 big endian), rom length (u16 big endian), font bytes, rom bytes
+    // and the font has to be a full 80 byte fontset
+    pub fn load_pack(&mut self, data: &[u8]) -> Result<(), EmulatorError> {
+        if data.len() < PACK_HEADER_SIZE || data[..4] != PACK_MAGIC {
+            return Err(EmulatorError::InvalidPack { reason: "missing C8PK header" });
+        }
+
+        let font_len = u16::from_be_bytes([data[4], data[5]]) as usize;
+        let rom_len = u16::from_be_bytes([data[6], data[7]]) as usize;
+        if data.len() != PACK_HEADER_SIZE + font_len + rom_len {
+            return Err(EmulatorError::InvalidPack { reason: "lengths in the header don't match the pack size" });
+        }
+
+        let font = &data[PACK_HEADER_SIZE..PACK_HEADER_SIZE + font_len];
+        let rom = &data[PACK_HEADER_SIZE + font_len..];
+        // check everything before touching ram so a bad pack changes nothing
+        if font_len != FONTSET_SIZE {
+            return Err(EmulatorError::InvalidFontset { len: font_len, expected: FONTSET_SIZE });
+        }
+        self.check_rom_size(rom.len())?;
+
+        self.set_fontset(font)?;
+        self.load(rom)
+    }
+
+    pub fn rom_len(&self) -> usize {
+        self.rom_len
+    }
+
+    // 64 bit FNV-1a hash of the loaded rom for looking it up in a rom database, None until a rom is loaded
+    pub fn rom_checksum(&self) -> Option<u64> {
+        if self.rom_len == 0 {
+            return None;
+        }
+
+        Some(self.rom_checksum)
+    }
+
+    pub fn total_ram(&self) -> usize {
+        RAM_SIZE
+    }
+
+    // ram left after the rom, everything below the start address belongs to the interpreter
+    pub fn free_ram(&self) -> usize {
+        (RAM_SIZE - self.start_addr as usize).saturating_sub(self.rom_len)
+    }
+
+    fn execute(&mut self, operation: u16) -> Result<(), EmulatorError> {
+        self.last_opcode = operation;
+
+        // match opcodes
+        match decode(operation) {
+            Instruction::Nop => (),
+            // 00E0 => clear display, only the planes FN01 selected
+            Instruction::ClearScreen => {
+                self.last_tick_drew = false;
+                if self.plane_mask & 0b01 != 0 {
+                    self.last_tick_drew |= self.screen.contains(&true);
+                    self.screen = [false; SCREEN_WIDTH * SCREEN_HEIGHT];
+                }
+                if self.plane_mask & 0b10 != 0 {
+                    self.last_tick_drew |= self.plane2.contains(&true);
+                    self.plane2 = [false; SCREEN_WIDTH * SCREEN_HEIGHT];
+                }
+            },
+            // 00EE => return from a subroutine
+            Instruction::Return => {
+                // get current stack pointer
+                match self.pop() {
+                    // get back to the current stack pointer
+                    Ok(subroutine_address) => self.program_counter = subroutine_address,
+                    Err(err) => self.tolerate(err)?,
+                }
+            },
+            // 1NNN => jump to an address NNN
+            Instruction::Jump { nnn } => {
+                self.program_counter = nnn;
+            },
+            // 2NNN => calls subroutine at NNN
+            Instruction::Call { nnn } => {
+                // return to current step after
+                match self.push(self.program_counter) {
+                    // go to address
+                    Ok(()) => self.program_counter = nnn,
+                    Err(err) => self.tolerate(err)?,
+                }
+            },
+            // 3XNN => skip next operation if vX == NN
+            Instruction::SkipIfEq { x, nn } => {
+                if self.v_registers[x] == nn {
+                    // skip 1 operation
+                    self.skip_next();
+                }
+            },
+            // 4XNN => skip next operation if vX != NN
+            Instruction::SkipIfNotEq { x, nn } => {
+                if self.v_registers[x] != nn {
+                    // skip 1 operation
+                    self.skip_next();
+                }
+            },
+            // 5XY0 => skip next operation if vX == vY
+            Instruction::SkipIfRegEq { x, y } => {
+                if self.v_registers[x] == self.v_registers[y] {
+                    // skip next operation
+                    self.skip_next();
+                }
+            },
+            // 6XNN => set vX to NN
+            Instruction::Set { x, nn } => {
+                self.v_registers[x] = nn;
+            },
+            // 7XNN => add vX to nn
+            Instruction::Add { x, nn } => {
+                // use .wrapping_add instead if .add because nn may overflow resulting in panic (crash)
+                // unlike 8XY4 the carry is thrown away, vF must not be touched here
+                self.v_registers[x] = self.v_registers[x].wrapping_add(nn);
+            },
+            // 8XY0 => sets vX to value of xY
+            Instruction::SetReg { x, y } => {
+                self.v_registers[x] = self.v_registers[y];
+            },
+            // 8XY1 => sets vX to the result of vX |= vY
+            // 8XY1/8XY2/8XY3 only ever write vX, plus vF with the vf_reset quirk
+            Instruction::Or { x, y } => {
+                self.v_registers[x] |= self.v_registers[y];
+                self.reset_vf();
+            },
+            // 8XY2 => sets vX to the result of vX &= vY
+            Instruction::And { x, y } => {
+                self.v_registers[x] &= self.v_registers[y];
+                self.reset_vf();
+            },
+            // 8XY3 => sets vX to the result of vX ^= vY
+            Instruction::Xor { x, y } => {
+                self.v_registers[x] ^= self.v_registers[y];
+                self.reset_vf();
+            },
+            // 8XY4 => Adds vY to vX, sets vF
+            // 8XY4 to 8XYE write the result to vX before setting vF, so with X = F the flag is what's left (as on the COSMAC VIP)
+            Instruction::AddReg { x, y } => {
+                let (new_x,overflowed) = self.v_registers[x].overflowing_add(self.v_registers[y]);
+                self.v_registers[x] = new_x;
+
+                if overflowed {
+                    self.v_registers[0xF] = 1
+                } else {
+                    self.v_registers[0xF] = 0
+                }
+            },
+             // 8XY5 => subtracts vY from vX, sets vF
+            Instruction::Sub { x, y } => {
+                let (nex_x,overflowed) = self.v_registers[x].overflowing_sub(self.v_registers[y]);
+                self.v_registers[x] = nex_x;
+
+                if overflowed {
+                    self.v_registers[0xF] = 0;
+                } else {
+                    self.v_registers[0xF] = 1;
+                }
+            },
+             // 8XY6 => shifts vX (vY with the shift_vy quirk) one bit to the right into vX, and sets vF
+             // the shifted out bit is taken before anything is written, so 8FF6/8FFE leave just the flag in vF
+            Instruction::ShiftRight { x, y } => {
+                let value = if self.quirks.shift_vy { self.v_registers[y] } else { self.v_registers[x] };
+                let least_significant_bit = value & 1;
+
+                self.v_registers[x] = value >> 1;
+                self.v_registers[0xF] = least_significant_bit;
+            },
+            // 8XY7 => subtracts vX from vY, sets vF
+            Instruction::SubReverse { x, y } => {
+                let (nex_x,overflowed) = self.v_registers[y].overflowing_sub(self.v_registers[x]);
+                self.v_registers[x] = nex_x;
+
+                if overflowed {
+                    self.v_registers[0xF] = 0;
+                } else {
+                    self.v_registers[0xF] = 1;
+                }
+            },
+            // 8XYE => shifts vX (vY with the shift_vy quirk) one bit to the left into vX, and sets vF
+            Instruction::ShiftLeft { x, y } => {
+                let value = if self.quirks.shift_vy { self.v_registers[y] } else { self.v_registers[x] };
+                let most_significat_bit = (value >> 7) & 1;
+
+                self.v_registers[x] = value << 1;
+                self.v_registers[0xF] = most_significat_bit;
+            },
+            // 9XY0 => skip next option if vX != vY
+            Instruction::SkipIfRegNotEq { x, y } => {
+                if self.v_registers[x] != self.v_registers[y] {
+                    self.skip_next();
+                }
+            },
+            // ANNN => sets i to nnn
+            Instruction::SetIndex { nnn } => {
+                self.i_register = nnn;
+            },
+            // BNNN => jump to the address of nnn + v[0], or XNN + vX with the jump_vx quirk
+            Instruction::JumpOffset { nnn } => {
+                let x = if self.quirks.jump_vx { (nnn >> 8) as usize } else { 0 };
+                self.program_counter = nnn + (self.v_registers[x] as u16);
+            },
+            // CXNN => set vx to a random value masked (bitwise AND) with NN
+            Instruction::Random { x, nn } => {
+                let random_number:u8 = if let Some(rng) = self.user_rng.as_mut() {
+                    rng.gen()
+                } else if let Some(rng) = self.rng.as_mut() {
+                    rng.gen()
+                } else {
+                    rand::thread_rng().gen()
+                };
+
+                self.v_registers[x] = random_number & nn;
+            },
+            // DXYN => Draws a sprite at coordinate (VX, VY) that has a width of 8 pixels and a height of N pixels. Each row of 8 pixels is read as bit-coded starting from memory location I; I value does not change after the execution of this instruction. As described above, VF is set to 1 if any screen pixels are flipped from set to unset when the sprite is drawn, and to 0 if that does not happen
+            Instruction::Draw { x, y, n } => {
+                // with the vblank quirk only one sprite is drawn per frame, retry this opcode until the timers tick
+                if self.quirks.vblank && self.drew_this_frame {
+                    self.program_counter -= 2;
+                    return Ok(());
+                }
+
+                // invariant: every sprite byte is read through ram_index, so the last one (I + sprite_len - 1) either
+                // lands inside ram or is a MemoryOutOfBounds under MemoryPolicy::Error. sprite_len is one byte per
+                // row and plane today, a 16x16 SUPER-CHIP sprite would read two per row and has to be counted here too.
+                // the last byte is checked up front so an error draws nothing
+                let plane_mask = self.plane_mask;
+                let sprite_len = n as usize * plane_mask.count_ones() as usize;
+                if sprite_len > 0 {
+                    self.ram_index(self.i_register as usize + sprite_len - 1)?;
+                }
+                self.drew_this_frame = true;
+                self.draw_calls += 1;
+
+                // get cords, copied out before anything is drawn so DXYN with X or Y = F uses vF's old value,
+                // vF itself is only written once the whole sprite is done
+                let (x_cord, y_cord) = if self.quirks.signed_coords {
+                    // read as i8 so 0xFF means -1
+                    (self.v_registers[x] as i8 as i32, self.v_registers[y] as i8 as i32)
+                } else {
+                    (self.v_registers[x] as i32, self.v_registers[y] as i32)
+                };
+
+                // number of rows is the last digit. DXY0 draws nothing and clears vF, on purpose: it is a 16x16 sprite
+                // on SUPER-CHIP, which has no high resolution mode here to route it to
+                let rows = n as u16;
+                // track the flipped flag
+                let mut flipped = false;
+
+                // with both planes selected plane2's rows follow the first plane's in ram, like XO-CHIP
+                let planes = (0..2).filter(|plane| plane_mask & (1 << plane) != 0);
+                for (drawn, plane) in planes.enumerate() {
+                    let first_row = self.i_register as usize + drawn * rows as usize;
+
+                    for row in 0..rows {
+                        let row_address = self.ram_index(first_row + row as usize)?;
+                        let pixels = self.ram[row_address];
+
+                        // 0..8 because each sprite width is 8px
+                        for col in 0..8 {
+                            // get current pixel's bit, flip if its a 1, do nothing if its a 0
+                            // cant exactly understand how the logic below works (hard copy)
+                            let is_flipped = pixels & (0b1000_0000 >> col) != 0;
+                            if is_flipped {
+                                let x = sprite_position(x_cord, col, SCREEN_WIDTH, self.quirks.wrap_x, self.quirks.signed_coords);
+                                let y = sprite_position(y_cord, row as i32, SCREEN_HEIGHT, self.quirks.wrap_y, self.quirks.signed_coords);
+                                let (x, y) = match (x, y) {
+                                    (Some(x), Some(y)) => (x, y),
+                                    _ => continue,
+                                };
+
+                                // Get our pixel's index for our 1D screen array
+                                let idx = x + SCREEN_WIDTH * y;
+                                let pixel = if plane == 0 { &mut self.screen[idx] } else { &mut self.plane2[idx] };
+                                match self.draw_mode {
+                                    DrawMode::Xor => {
+                                        // Check if we're about to flip the pixel and set
+                                        flipped |= *pixel;
+                                        *pixel ^= true;
+                                        self.last_tick_drew = true;
+                                    },
+                                    DrawMode::Or => {
+                                        self.last_tick_drew |= !*pixel;
+                                        *pixel = true;
+                                    },
+                                }
+                            }
+                        }
+                    }
+                }
+
+                // vF is the only register DXYN writes, vX, vY and I are left as they were
+                if flipped {
+                    self.v_registers[0xF] = 1;
+                } else {
+                    self.v_registers[0xF] = 0;
+                }
+
+            },
+            // EX9E => skip on key press
+            Instruction::SkipIfKey { x } => {
+                let key_pressed = self.key_state(self.v_registers[x])?;
+
+                if key_pressed {
+                    self.skip_next();
+                }
+            },
+            // EXA1 => skip if key is not pressed
+            Instruction::SkipIfNotKey { x } => {
+                let key_pressed = self.key_state(self.v_registers[x])?;
+
+                if !key_pressed {
+                    self.skip_next();
+                }
+            },
+            // FX07 => sets delay timer
+            Instruction::GetDelay { x } => {
+                self.v_registers[x] = self.delay_timer;
+            },
+            // FX0A => wait for key press
+            Instruction::WaitKey { x } => {
+                let mut key_pressed = false;
+
+                // when several keys are held the most recently pressed one wins
+                let newest_key = (0..NUM_KEYS)
+                    .filter(|&i| self.keys[i])
+                    .max_by_key(|&i| self.key_press_order[i]);
+
+                if let Some(i) = newest_key {
+                    self.v_registers[x] = i as u8;
+                    key_pressed = true;
+                }
+
+                // using this flag because in a loop our code would not be able to process a key press, turning into infinity loop (from guide)
+                if !key_pressed {
+                    // retry opcode
+                    self.program_counter -= 2;
+                }
+            },
+            // FN01 => selects the planes 00E0 and DXYN work on (XO-CHIP), there are only two so N's upper bits are ignored
+            Instruction::SelectPlanes { mask } => {
+                self.plane_mask = mask & 0b11;
+            },
+            // FX15 => set delay timer to vX
+            Instruction::SetDelay { x } => {
+                self.delay_timer = self.v_registers[x];
+            },
+            // FX18 => set sound timer to vX
+            Instruction::SetSound { x } => {
+                self.sound_timer = self.v_registers[x];
+            },
+            // FX1E => adds vX to I
+            Instruction::AddIndex { x } => {
+                self.i_register = self.i_register.wrapping_add(self.v_registers[x] as u16);
+            },
+            // FX29 => sets I to font address
+            Instruction::FontChar { x } => {
+                let character_position = self.v_registers[x] as u16;
+
+                // times 5 because each font is 5 bytes each
+                self.i_register = character_position * 5;
+            },
+            // FX33 => Stores the binary-coded decimal representation of VX, with the hundreds digit in memory at location in I, the tens digit at location I+1, and the ones digit at location I+2
+            Instruction::Bcd { x } => {
+                let v_x = self.v_registers[x];
+                let offset = self.i_register as usize;
+
+                let hundreds = v_x / 100;
+                let tens = (v_x / 10) % 10;
+                let ones = v_x % 10;
+
+                // check the last address up front so an error leaves ram untouched, like FX55
+                self.ram_index(offset + 2)?;
+
+                for (i, digit) in [hundreds, tens, ones].into_iter().enumerate() {
+                    let address = self.ram_index(offset + i)?;
+                    self.store(address, digit);
+                }
+            },
+            // FX55 => Stores from V0 to VX (including VX) in memory, starting at address I. The offset from I is increased by 1 for each value written, but I itself is left unmodified unless the increment_i quirk is on
+            Instruction::Store { x } => {
+                let offset = self.i_register as usize;
+
+                // check the last address up front so an error leaves ram untouched
+                self.ram_index(offset + x)?;
+
+                // ..= (including vX)
+                for i in 0..=x {
+                    // store in memory (ram)
+                    let address = self.ram_index(offset + i)?;
+                    self.store(address, self.v_registers[i]);
+                }
+
+                if self.quirks.increment_i {
+                    self.i_register = self.i_register.wrapping_add(x as u16 + 1);
+                }
+            },
+            // FX65 => Fills from V0 to VX (including VX) with values from memory, starting at address I. The offset from I is increased by 1 for each value read, but I itself is left unmodified unless the increment_i quirk is on
+            Instruction::Load { x } => {
+                let offset = self.i_register as usize;
+
+                self.ram_index(offset + x)?;
+
+                // ..= (including vX)
+                for i in 0..=x {
+                    // store in memory (ram)
+                    let address = self.ram_index(offset + i)?;
+                    self.v_registers[i] = self.ram[address];
+                }
+
+                if self.quirks.increment_i {
+                    self.i_register = self.i_register.wrapping_add(x as u16 + 1);
+                }
+            },
+            // FX75 => stores V0 to VX in the RPL user flags, X above 7 is capped since there are only 8 flags
+            Instruction::SaveFlags { x } => {
+                let last = x.min(NUM_RPL_FLAGS - 1);
+                self.rpl_flags[..=last].copy_from_slice(&self.v_registers[..=last]);
+            },
+            // FX85 => fills V0 to VX from the RPL user flags, X above 7 is capped the same way
+            Instruction::LoadFlags { x } => {
+                let last = x.min(NUM_RPL_FLAGS - 1);
+                self.v_registers[..=last].copy_from_slice(&self.rpl_flags[..=last]);
+            },
+            Instruction::Unknown { .. } => {
+                // pc already moved past the opcode in fetch
+                let pc = self.program_counter.wrapping_sub(2);
+                self.tolerate(EmulatorError::UnknownOpcode { opcode: operation, pc })?;
+            },
+        }
+
+        Ok(())
+    }
+
+    // writes a byte the rom asked for, recording it if the address is watched
+    fn store(&mut self, address: usize, value: u8) {
+        self.ram[address] = value;
+
+        if self.detect_smc {
+            self.rom_written[address / 64] |= 1 << (address % 64);
+        }
+
+        if self.watched_addrs.contains(&(address as u16)) {
+            self.watch_hits.push((self.cycle_count, address as u16, value));
+        }
+    }
+
+    // maps an I based address into ram according to the memory policy
+    fn ram_index(&self, address: usize) -> Result<usize, EmulatorError> {
+        if address < RAM_SIZE {
+            return Ok(address);
+        }
+
+        match self.memory_policy {
+            MemoryPolicy::Error => Err(EmulatorError::MemoryOutOfBounds { address }),
+            MemoryPolicy::Wrap => Ok(address % RAM_SIZE),
+        }
+    }
+
+    pub fn update_timers(&mut self) {
+        self.instructions_this_frame = 0;
+        self.drew_this_frame = false;
+
+        // the frame that just ended is what gets recorded, then the next recorded frame's keys go in
+        let snapshot = self.frame_input_snapshot();
+        if let Some(recording) = self.input_recording.as_mut() {
+            recording.push(snapshot);
+        }
+        self.apply_next_input_frame();
+
+        if self.delay_timer > 0 {
+            self.delay_timer -=1;
+        }
+
+        if self.sound_timer > 0 {
+            self.sound_timer -=1;
+        }
+
+        if self.frame_rewind {
+            if self.frame_snapshots.len() == REWIND_FRAMES {
+                self.frame_snapshots.pop_front();
+            }
+            let snapshot = self.frame_snapshot();
+            self.frame_snapshots.push_back(snapshot);
+        }
+    }
+
+    // keeps a snapshot of the last REWIND_FRAMES frame boundaries for step_back_frame, about 10KB each
+    // (ram, both screen buffers and plane2)
+    pub fn set_frame_rewind(&mut self, enabled: bool) {
+        self.frame_rewind = enabled;
+        if !enabled {
+            self.frame_snapshots.clear();
+        }
+    }
+
+    // goes back to the previous frame boundary, or the start of the current frame when it is part way through.
+    // false when there is no earlier frame to go back to. input playback is not rewound
+    pub fn step_back_frame(&mut self) -> bool {
+        // right after update_timers (or a step back) the newest snapshot is the state we are already in
+        let at_boundary = self.instructions_this_frame == 0 && !self.frame_snapshots.is_empty();
+        let needed = if at_boundary { 2 } else { 1 };
+        if self.frame_snapshots.len() < needed {
+            return false;
+        }
+
+        if at_boundary {
+            self.frame_snapshots.pop_back();
+        }
+        // left in the ring, so the next step back knows it is sitting on this boundary
+        let snapshot = match self.frame_snapshots.back() {
+            Some(snapshot) => snapshot.clone(),
+            None => return false,
+        };
+        self.restore_frame_snapshot(snapshot);
+
+        true
+    }
+
+    fn frame_snapshot(&self) -> FrameSnapshot {
+        FrameSnapshot {
+            program_counter: self.program_counter,
+            ram: self.ram,
+            screen: self.screen,
+            front_screen: self.front_screen,
+            plane2: self.plane2,
+            plane_mask: self.plane_mask,
+            v_registers: self.v_registers,
+            i_register: self.i_register,
+            stack_pointer: self.stack_pointer,
+            stack: self.stack,
+            delay_timer: self.delay_timer,
+            sound_timer: self.sound_timer,
+            cycle_count: self.cycle_count,
+            draw_calls: self.draw_calls,
+            rpl_flags: self.rpl_flags,
+            rng: self.rng.clone(),
+        }
+    }
+
+    fn restore_frame_snapshot(&mut self, snapshot: FrameSnapshot) {
+        self.program_counter = snapshot.program_counter;
+        self.ram = snapshot.ram;
+        self.screen = snapshot.screen;
+        self.front_screen = snapshot.front_screen;
+        self.plane2 = snapshot.plane2;
+        self.plane_mask = snapshot.plane_mask;
+        self.v_registers = snapshot.v_registers;
+        self.i_register = snapshot.i_register;
+        self.stack_pointer = snapshot.stack_pointer;
+        self.stack = snapshot.stack;
+        self.delay_timer = snapshot.delay_timer;
+        self.sound_timer = snapshot.sound_timer;
+        self.cycle_count = snapshot.cycle_count;
+        self.draw_calls = snapshot.draw_calls;
+        self.rpl_flags = snapshot.rpl_flags;
+        self.rng = snapshot.rng;
+        self.instructions_this_frame = 0;
+        self.drew_this_frame = false;
+        self.last_tick_drew = false;
+        self.skipped_addr = None;
+    }
+
+    // time based alternative to update_timers, counts the timers down once for every 1/hz of elapsed time
+    pub fn update_timers_elapsed(&mut self, elapsed: Duration) {
+        let period = Duration::from_secs(1) / self.timer_frequency;
+
+        self.timer_elapsed += elapsed;
+        while self.timer_elapsed >= period {
+            self.timer_elapsed -= period;
+            self.update_timers();
+        }
+    }
+
+    // front-ends can save these to disk and hand them back with set_rpl_flags, like the HP48 did
+    pub fn rpl_flags(&self) -> [u8; NUM_RPL_FLAGS] {
+        self.rpl_flags
+    }
+
+    pub fn set_rpl_flags(&mut self, flags: [u8; NUM_RPL_FLAGS]) {
+        self.rpl_flags = flags;
+    }
+
+    pub fn delay_timer(&self) -> u8 {
+        self.delay_timer
+    }
+
+    pub fn set_delay_timer(&mut self, value: u8) {
+        self.delay_timer = value;
+    }
+
+    pub fn sound_timer(&self) -> u8 {
+        self.sound_timer
+    }
+
+    pub fn set_sound_timer(&mut self, value: u8) {
+        self.sound_timer = value;
+    }
+
+    // front-ends should play a tone for as long as this is true
+    pub fn is_beeping(&self) -> bool {
+        self.sound_enabled && self.sound_timer > 0
+    }
+
+    // how much longer the current tone lasts at the configured timer frequency
+    pub fn beep_duration_remaining(&self) -> Duration {
+        if !self.sound_enabled {
+            return Duration::ZERO;
+        }
+
+        Duration::from_secs(self.sound_timer as u64) / self.timer_frequency
+    }
+
+    // mutes the beep, the sound timer keeps counting down so roms that time things with it run the same
+    pub fn set_sound_enabled(&mut self, enabled: bool) {
+        self.sound_enabled = enabled;
+    }
+
+    pub fn sound_enabled(&self) -> bool {
+        self.sound_enabled
+    }
+
+    fn fetch(&mut self) -> Result<u16, EmulatorError> {
+        if self.strict_alignment && self.program_counter & 1 != 0 {
+            return Err(EmulatorError::MisalignedPc { pc: self.program_counter });
+        }
+
+        // running off the end of ram (or a BNNN past it) is an error in lax mode too, there is nothing to skip to
+        if self.program_counter as usize + 1 >= RAM_SIZE {
+            return Err(EmulatorError::PcOutOfBounds { pc: self.program_counter });
+        }
+
+        // get current operation take 2 because each ram item is 8 bytes
+        let first_byte = self.ram[self.program_counter as usize];
+        let second_byte = self.ram[(self.program_counter + 1) as usize];
+
+        if self.detect_smc {
+            self.check_smc(self.program_counter);
+        }
+
+        // update program position
+        self.program_counter += 2;
+
+        // return operation
+        Ok(self.combine_bytes(first_byte, second_byte))
+    }
+
+    // records pc when either byte of the opcode there was written by the rom
+    fn check_smc(&mut self, pc: u16) {
+        let written = |address: usize| self.rom_written[address / 64] & (1 << (address % 64)) != 0;
+
+        if (written(pc as usize) || written(pc as usize + 1)) && !self.smc_events.contains(&pc) {
+            self.smc_events.push(pc);
+        }
+    }
+
+    // Combines the bytes into one following the fetch endianness
+    fn combine_bytes(&self, first_byte: u8, second_byte: u8) -> u16 {
+        match self.fetch_endianness {
+            Endianness::Big => ((first_byte as u16) << 8) | second_byte as u16,
+            Endianness::Little => ((second_byte as u16) << 8) | first_byte as u16,
+        }
+    }
+
+    // a skip instruction taking its branch, remembered for skipped_opcode
+    fn skip_next(&mut self) {
+        self.skipped_addr = Some(self.program_counter);
+        self.program_counter += 2;
+    }
+
+    // the vf_reset quirk clears vF after the 8XY1/8XY2/8XY3 logic ops
+    fn reset_vf(&mut self) {
+        if self.quirks.vf_reset {
+            self.v_registers[0xF] = 0;
+        }
+    }
+
+    // EX9E/EXA1 read the key number from a register, which can hold more than 0xF. lax mode only looks at
+    // the low nibble, like the COSMAC VIP's keypad latch did
+    fn key_state(&self, key: u8) -> Result<bool, EmulatorError> {
+        match self.keys.get(key as usize) {
+            Some(pressed) => Ok(*pressed),
+            None if self.strict => Err(EmulatorError::InvalidKeyIndex { index: key as usize }),
+            None => {
+                warn!("key {:#X} is out of range, checking key {:X}", key, key & 0xF);
+                Ok(self.keys[(key & 0xF) as usize])
+            },
+        }
+    }
+
+    // errors set_strict(false) lets through, the instruction is skipped instead
+    fn tolerate(&self, error: EmulatorError) -> Result<(), EmulatorError> {
+        if self.strict {
+            return Err(error);
+        }
+
+        warn!("skipping instruction: {}", error);
+        Ok(())
+    }
+
+    fn push(&mut self, val: u16) -> Result<(), EmulatorError> {
+        if self.stack_pointer as usize >= STACK_SIZE {
+            return Err(EmulatorError::StackOverflow { pc: self.program_counter.wrapping_sub(2) });
+        }
+
+        self.stack[self.stack_pointer as usize] = val;
+        self.stack_pointer += 1;
+
+        if self.stack_pointer as usize >= STACK_SIZE - 2 {
+            warn!("stack nearly full: {} of {} slots used", self.stack_pointer, STACK_SIZE);
+        }
+
+        Ok(())
+    }
+
+    fn pop(&mut self) -> Result<u16, EmulatorError> {
+        if self.stack_pointer == 0 {
+            return Err(EmulatorError::StackUnderflow { pc: self.program_counter.wrapping_sub(2) });
+        }
+
+        self.stack_pointer -= 1;
+        Ok(self.stack[self.stack_pointer as usize])
+    }
+}
+
+// the COSMAC VIP keypad, keypad_layout()[row][column] is the hex key at that spot. front-ends map their own keys
+// (a 4x4 block of a keyboard, on screen buttons) to positions and look the hex value up here
+pub fn keypad_layout() -> [[u8; 4]; 4] {
+    KEYPAD_LAYOUT
+}
+
+// (row, column) of a hex key in keypad_layout, None for anything above 0xF
+pub fn keypad_position(key: u8) -> Option<(usize, usize)> {
+    KEYPAD_LAYOUT.iter().enumerate().find_map(|(row, keys)| {
+        keys.iter().position(|&k| k == key).map(|column| (row, column))
+    })
+}
+
+// (x, y) of every pixel the two displays disagree on, row by row, for differential testing against another core
+pub fn screen_diff(a: &Emulator, b: &Emulator) -> Vec<(usize, usize)> {
+    a.get_display()
+        .iter()
+        .zip(b.get_display())
+        .enumerate()
+        .filter(|(_, (pixel_a, pixel_b))| pixel_a != pixel_b)
+        .map(|(i, _)| (i % SCREEN_WIDTH, i / SCREEN_WIDTH))
+        .collect()
+}
+
+// 64 bit FNV-1a, simple and stable across versions so checksums can be stored
+fn fnv1a(data: &[u8]) -> u64 {
+    const OFFSET_BASIS: u64 = 0xCBF2_9CE4_8422_2325;
+    const PRIME: u64 = 0x0000_0100_0000_01B3;
+
+    data.iter().fold(OFFSET_BASIS, |hash, &byte| (hash ^ byte as u64).wrapping_mul(PRIME))
+}
+
+// works out where a sprite pixel lands on one axis, None when it falls off a clipped edge
+fn sprite_position(start: i32, offset: i32, size: usize, wrap: bool, signed: bool) -> Option<usize> {
+    let size = size as i32;
+    if wrap {
+        return Some((start + offset).rem_euclid(size) as usize);
+    }
+
+    // start position still wraps, anything hanging off the edge is dropped. a negative signed start is
+    // left alone so the sprite hangs off the top/left edge instead
+    let start = if signed && start < 0 { start } else { start % size };
+    let position = start + offset;
+    if position < 0 || position >= size {
+        None
+    } else {
+        Some(position as usize)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // opcodes as big endian rom bytes
+    fn rom_bytes(opcodes: &[u16]) -> Vec<u8> {
+        opcodes.iter().flat_map(|operation| operation.to_be_bytes()).collect()
+    }
+
+    // an emulator with opcodes loaded at the start address
+    fn emulator_with(opcodes: &[u16]) -> Emulator {
+        let mut emulator = Emulator::new();
+        emulator.load(&rom_bytes(opcodes)).unwrap();
+
+        emulator
+    }
+
+    fn run(emulator: &mut Emulator, ticks: usize) {
+        for _ in 0..ticks {
+            emulator.tick().unwrap();
+        }
+    }
+
+    // every register holds a different value, so any stray write shows up
+    fn fill_registers(emulator: &mut Emulator) {
+        for idx in 0..NUM_REGISTERS {
+            emulator.set_v_register(idx, 0x10 + idx as u8).unwrap();
+        }
+    }
+
+    // registers that differ between two snapshots
+    fn changed_registers(before: [u8; NUM_REGISTERS], after: [u8; NUM_REGISTERS]) -> Vec<usize> {
+        (0..NUM_REGISTERS).filter(|&idx| before[idx] != after[idx]).collect()
+    }
+
+    mod opcode_side_effects {
+        use super::*;
+
+        #[test]
+        fn add_immediate_does_not_set_vf_on_carry() {
+            // v0 = FF, vF = 5, v0 += 1
+            let mut emulator = emulator_with(&[0x60FF, 0x6F05, 0x7001]);
+            run(&mut emulator, 3);
+
+            assert_eq!(emulator.v_register(0), Some(0));
+            assert_eq!(emulator.v_register(0xF), Some(5));
+        }
+
+        #[test]
+        fn add_immediate_only_writes_vx() {
+            let mut emulator = emulator_with(&[0x73F0]);
+            fill_registers(&mut emulator);
+            let before = emulator.registers_snapshot();
+            run(&mut emulator, 1);
+
+            assert_eq!(changed_registers(before, emulator.registers_snapshot()), vec![3]);
+        }
+
+        #[test]
+        fn logic_ops_only_write_vx() {
+            for operation in [0x8121, 0x8122, 0x8123] {
+                let mut emulator = emulator_with(&[operation]);
+                fill_registers(&mut emulator);
+                let before = emulator.registers_snapshot();
+                run(&mut emulator, 1);
+
+                assert_eq!(changed_registers(before, emulator.registers_snapshot()), vec![1], "{:04X}", operation);
+            }
+        }
+
+        #[test]
+        fn logic_ops_with_vf_reset_also_clear_vf() {
+            for operation in [0x8121, 0x8122, 0x8123] {
+                let mut emulator = emulator_with(&[operation]);
+                emulator.set_quirk("vf_reset", true).unwrap();
+                fill_registers(&mut emulator);
+                let before = emulator.registers_snapshot();
+                run(&mut emulator, 1);
+
+                assert_eq!(changed_registers(before, emulator.registers_snapshot()), vec![1, 0xF], "{:04X}", operation);
+                assert_eq!(emulator.v_register(0xF), Some(0));
+            }
+        }
+
+        #[test]
+        fn draw_only_writes_vf() {
+            // draw the "0" glyph at (v1, v2) twice, the second one erases the first and sets vF
+            let mut emulator = emulator_with(&[0xA000, 0xD125, 0xD125]);
+            fill_registers(&mut emulator);
+            emulator.set_v_register(0xF, 0x7F).unwrap();
+            let before = emulator.registers_snapshot();
+            run(&mut emulator, 2);
+
+            assert_eq!(changed_registers(before, emulator.registers_snapshot()), vec![0xF]);
+            assert_eq!(emulator.v_register(0xF), Some(0));
+            assert_eq!(emulator.i_register(), 0);
+
+            run(&mut emulator, 1);
+            assert_eq!(changed_registers(before, emulator.registers_snapshot()), vec![0xF]);
+            assert_eq!(emulator.v_register(0xF), Some(1));
+        }
+    }
+
+    #[test]
+    fn draw_reads_coordinates_from_vf_before_setting_the_flag() {
+        // vF = 10, I = "0" glyph, draw at (vF, vF)
+        let mut emulator = emulator_with(&[0x6F0A, 0xA000, 0xDFF5]);
+        run(&mut emulator, 3);
+
+        assert!(emulator.get_pixel(10, 10));
+        assert!(!emulator.get_pixel(0, 0));
+        assert_eq!(emulator.lit_bounds(), Some((10, 10, 13, 14)));
+        assert_eq!(emulator.v_register(0xF), Some(0));
+    }
+
+    // runs operation with vF and v1 preset, returning vF afterwards
+    fn vf_after(operation: u16, vf: u8, v1: u8) -> u8 {
+        let mut emulator = emulator_with(&[operation]);
+        emulator.set_v_register(0xF, vf).unwrap();
+        emulator.set_v_register(1, v1).unwrap();
+        run(&mut emulator, 1);
+
+        emulator.v_register(0xF).unwrap()
+    }
+
+    #[test]
+    fn add_with_x_f_keeps_the_carry() {
+        // FF + 1 carries, 1 + 2 = 3 doesn't
+        assert_eq!(vf_after(0x8F14, 0xFF, 1), 1);
+        assert_eq!(vf_after(0x8F14, 1, 2), 0);
+    }
+
+    #[test]
+    fn sub_with_x_f_keeps_the_flag() {
+        // 5 - 3 has no borrow, 3 - 5 does
+        assert_eq!(vf_after(0x8F15, 5, 3), 1);
+        assert_eq!(vf_after(0x8F15, 3, 5), 0);
+    }
+
+    #[test]
+    fn reverse_sub_with_x_f_keeps_the_flag() {
+        // v1 - vF: 5 - 3 has no borrow, 3 - 5 does
+        assert_eq!(vf_after(0x8F17, 3, 5), 1);
+        assert_eq!(vf_after(0x8F17, 5, 3), 0);
+    }
+
+    #[test]
+    fn shift_right_with_x_f_keeps_the_shifted_out_bit() {
+        // 0b11 >> 1 would leave 1 either way, 0b10 >> 1 shows the flag (0) beat the result (1)
+        assert_eq!(vf_after(0x8F06, 0b11, 0), 1);
+        assert_eq!(vf_after(0x8F06, 0b10, 0), 0);
+    }
+
+    #[test]
+    fn shift_left_with_x_f_keeps_the_shifted_out_bit() {
+        // 0x81 << 1 = 0x02 with the flag 1, 0x40 << 1 = 0x80 with the flag 0
+        assert_eq!(vf_after(0x8F0E, 0x81, 0), 1);
+        assert_eq!(vf_after(0x8F0E, 0x40, 0), 0);
+    }
+
+    #[test]
+    fn draw_with_zero_height_draws_nothing_and_clears_vf() {
+        // vF = 1, I = "0" glyph, D010
+        let mut emulator = emulator_with(&[0x6F01, 0xA000, 0xD010]);
+        run(&mut emulator, 3);
+
+        assert_eq!(emulator.lit_pixel_count(), 0);
+        assert!(!emulator.last_tick_drew());
+        assert_eq!(emulator.v_register(0xF), Some(0));
+    }
+
+    #[test]
+    fn reset_during_key_wait_leaves_no_stale_press() {
+        let mut emulator = emulator_with(&[0xF00A]);
+        run(&mut emulator, 2);
+        assert_eq!(emulator.pc(), START_ADDR);
+
+        // pressed while the old rom waits, but reset before it could see it
+        emulator.keypress(5, true).unwrap();
+        emulator.reset();
+        emulator.load(&[0xF1, 0x0A]).unwrap();
+        assert_eq!(emulator.pc(), START_ADDR);
+
+        run(&mut emulator, 2);
+        assert_eq!(emulator.pc(), START_ADDR);
+        assert_eq!(emulator.v_register(1), Some(0));
+        assert_eq!(emulator.keys_mask(), 0);
+    }
+
+    #[test]
+    fn load_and_reset_during_key_wait_leaves_no_stale_press() {
+        let mut emulator = emulator_with(&[0xF00A]);
+        run(&mut emulator, 1);
+        emulator.keypress(5, true).unwrap();
+        emulator.load_and_reset(&[0xF1, 0x0A]).unwrap();
+
+        run(&mut emulator, 2);
+        assert_eq!(emulator.pc(), START_ADDR);
+        assert_eq!(emulator.v_register(1), Some(0));
+    }
+
+    #[test]
+    fn font_digits_draw_their_glyphs() {
+        for digit in 0..=0xF {
+            // v0 = digit, I = glyph of v0, draw 5 rows at (v1, v2) = (0, 0)
+            let mut emulator = emulator_with(&[0x6000 | digit, 0xF029, 0xD125]);
+            run(&mut emulator, 3);
+
+            for row in 0..5 {
+                let bits = FONTSET[digit as usize * 5 + row];
+                for col in 0..8 {
+                    let expected = bits & (0x80 >> col) != 0;
+                    assert_eq!(emulator.get_pixel(col, row), expected, "digit {:X} at ({}, {})", digit, col, row);
+                }
+            }
+            assert_eq!(emulator.lit_bounds().map(|(_, _, _, max_y)| max_y), Some(4));
+        }
+    }
+
+    #[test]
+    fn bcd_stores_the_three_digits() {
+        // v0 = 123, I = 0x300
+        let mut emulator = emulator_with(&[0x607B, 0xA300, 0xF033]);
+        run(&mut emulator, 3);
+
+        assert_eq!(emulator.memory_dump(0x300, 3), vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn bcd_past_the_end_of_ram_errors_in_strict_mode() {
+        let mut emulator = emulator_with(&[0x607B, 0xAFFF, 0xF033]);
+        run(&mut emulator, 2);
+
+        assert!(matches!(emulator.tick(), Err(EmulatorError::MemoryOutOfBounds { address: 0x1001 })));
+        assert_eq!(emulator.memory_dump(0xFFF, 1), vec![0]);
+    }
+
+    #[test]
+    fn bcd_past_the_end_of_ram_wraps_in_lax_mode() {
+        let mut emulator = emulator_with(&[0x607B, 0xAFFF, 0xF033]);
+        emulator.set_strict(false);
+        run(&mut emulator, 3);
+
+        assert_eq!(emulator.memory_dump(0xFFF, 1), vec![1]);
+        assert_eq!(emulator.memory_dump(0, 2), vec![2, 3]);
+    }
+
+    #[test]
+    fn strict_mode_errors_where_lax_mode_skips() {
+        // 8FF9 doesn't exist, then v0 = 1
+        let mut strict = emulator_with(&[0x8FF9, 0x6001]);
+        assert!(matches!(strict.tick(), Err(EmulatorError::UnknownOpcode { opcode: 0x8FF9, pc: 0x200 })));
+
+        let mut lax = emulator_with(&[0x8FF9, 0x6001]);
+        lax.set_strict(false);
+        run(&mut lax, 2);
+        assert_eq!(lax.v_register(0), Some(1));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn bincode_round_trip_ticks_the_same() {
+        // random registers, then draw the glyph of v0 at (v1, v2)
+        let rom = [0xC0FF, 0xC1FF, 0xC2FF, 0xC3FF, 0xF029, 0xD125, 0xC4FF, 0xF433];
+        let mut original = Emulator::with_seed(7);
+        original.load(&rom_bytes(&rom)).unwrap();
+        run(&mut original, 2);
+
+        let bytes = bincode::serialize(&original).unwrap();
+        let mut restored: Emulator = bincode::deserialize(&bytes).unwrap();
+
+        for _ in 2..rom.len() {
+            original.tick().unwrap();
+            restored.tick().unwrap();
+            assert_eq!(restored.registers_snapshot(), original.registers_snapshot());
+            assert_eq!(restored.pc(), original.pc());
+            assert_eq!(restored.i_register(), original.i_register());
+        }
+        assert_eq!(restored.ram_image(), original.ram_image());
+        assert_eq!(restored.get_display(), original.get_display());
+    }
+
+    #[test]
+    fn start_address_inside_the_fontset_is_refused() {
+        let mut emulator = Emulator::new();
+
+        assert!(matches!(emulator.set_start_addr(0x20), Err(EmulatorError::StartAddressInFont { addr: 0x20 })));
+        assert!(emulator.set_start_addr(FONTSET_SIZE as u16).is_ok());
+    }
+
+    #[test]
+    fn low_start_address_keeps_the_fontset() {
+        let mut emulator = Emulator::new();
+        emulator.set_start_addr(FONTSET_SIZE as u16).unwrap();
+        emulator.prepare_for_rom(CompatProfile::Vip);
+        emulator.load_clean(&rom_bytes(&[0x00E0])).unwrap();
+
+        assert_eq!(&emulator.ram[..FONTSET_SIZE], &FONTSET[..]);
+    }
+
+    #[test]
+    fn timer_frequency_has_to_give_a_nonzero_period() {
+        let mut emulator = Emulator::new();
+
+        assert!(emulator.set_timer_frequency(0).is_err());
+        assert!(emulator.set_timer_frequency(2_000_000_000).is_err());
+        assert!(emulator.set_timer_frequency(1_000_000_000).is_ok());
+    }
+
+    #[test]
+    fn timers_count_down_at_the_set_frequency() {
+        // delay timer = 30
+        let mut emulator = emulator_with(&[0x601E, 0xF015]);
+        run(&mut emulator, 2);
+        emulator.set_timer_frequency(30).unwrap();
+        emulator.update_timers_elapsed(Duration::from_millis(500));
+
+        assert_eq!(emulator.delay_timer, 15);
+    }
+
+    #[test]
+    fn slots_switch_between_roms() {
+        // slot 0 sets v0 = 1, slot 1 sets v0 = 2
+        let mut emulator = Emulator::new();
+        emulator.load_slot(0, &rom_bytes(&[0x6001])).unwrap();
+        emulator.load_slot(1, &rom_bytes(&[0x6002])).unwrap();
+
+        emulator.activate_slot(1).unwrap();
+        run(&mut emulator, 1);
+        assert_eq!(emulator.v_register(0), Some(2));
+
+        emulator.activate_slot(0).unwrap();
+        run(&mut emulator, 1);
+        assert_eq!(emulator.v_register(0), Some(1));
+    }
+
+    #[test]
+    fn huge_slot_index_is_refused() {
+        let mut emulator = Emulator::new();
+
+        assert!(matches!(
+            emulator.load_slot(usize::MAX, &rom_bytes(&[0x00E0])),
+            Err(EmulatorError::RomSlotOutOfRange { slot: usize::MAX, max: MAX_ROM_SLOTS })
+        ));
+        assert!(matches!(emulator.activate_slot(2), Err(EmulatorError::EmptyRomSlot { slot: 2 })));
+    }
+
+    // lit columns of the top display row after drawing the top row of the 0 glyph (F0) at vX = x,
+    // with signed coordinates and the x axis clipped
+    fn signed_clipped_row(x: u8) -> Vec<usize> {
+        // I = 0 (glyph 0), v0 = x, v1 = 0, draw one row
+        let mut emulator = emulator_with(&[0xA000, 0x6000 | x as u16, 0x6100, 0xD011]);
+        emulator.set_quirks(Quirks { signed_coords: true, wrap_x: false, ..Quirks::default() });
+        run(&mut emulator, 4);
+
+        (0..SCREEN_WIDTH).filter(|&col| emulator.get_display()[col]).collect()
+    }
+
+    #[test]
+    fn signed_negative_start_hangs_off_the_clipped_edge() {
+        // 0xFD is -3, so only the last of the four lit pixels is on screen
+        assert_eq!(signed_clipped_row(0xFD), vec![0]);
+    }
+
+    #[test]
+    fn signed_positive_start_still_wraps_onto_the_screen() {
+        // 0x50 is 80, which starts the sprite at 80 % 64 = 16
+        assert_eq!(signed_clipped_row(0x50), vec![16, 17, 18, 19]);
+    }
+
+    #[test]
+    fn out_of_range_key_errors_in_strict_mode_and_is_masked_in_lax_mode() {
+        // v0 = 0x15, skip if key v0 is pressed, v1 = 1
+        let rom = [0x6015, 0xE09E, 0x6101];
+        let mut strict = emulator_with(&rom);
+        run(&mut strict, 1);
+        assert!(matches!(strict.tick(), Err(EmulatorError::InvalidKeyIndex { index: 0x15 })));
+
+        // key 5 is the low nibble of 0x15, so its press skips v1 = 1
+        let mut lax = emulator_with(&rom);
+        lax.set_strict(false);
+        lax.keypress(5, true).unwrap();
+        run(&mut lax, 3);
+        assert_eq!(lax.v_register(1), Some(0));
+    }
+
+    #[test]
+    fn unsupported_opcodes_reports_addresses_from_the_start_address() {
+        let emulator = Emulator::new();
+
+        assert_eq!(emulator.unsupported_opcodes(&rom_bytes(&[0x00E0, 0x00FF])), vec![(0x202, 0x00FF)]);
+    }
+
+    #[test]
+    fn unsupported_opcodes_handles_roms_past_64k() {
+        let emulator = Emulator::new();
+        let found = emulator.unsupported_opcodes(&rom_bytes(&[0x00FF; 40_000]));
+
+        // only the words below address 0x10000 have an address
+        assert_eq!(found.len(), (0x10000 - START_ADDR as usize) / 2);
+        assert_eq!(found.last(), Some(&(0xFFFE, 0x00FF)));
+    }
+
+    #[test]
+    fn sprite_near_the_top_of_ram_errors_in_strict_mode() {
+        // I = FFE, draw 5 rows, the last of which would be at 0x1002. it's checked before anything is drawn
+        let mut strict = emulator_with(&[0xAFFE, 0xD005]);
+        run(&mut strict, 1);
+
+        assert!(matches!(strict.tick(), Err(EmulatorError::MemoryOutOfBounds { address: 0x1002 })));
+        assert!(!strict.get_display().contains(&true));
+    }
+
+    #[test]
+    fn sprite_near_the_top_of_ram_wraps_in_lax_mode() {
+        // rows 0 and 1 come from the empty FFE/FFF, rows 2 to 4 wrap to the 0 glyph's F0 90 90
+        let mut lax = emulator_with(&[0xAFFE, 0xD005]);
+        lax.set_strict(false);
+        run(&mut lax, 2);
+
+        let lit: Vec<usize> = (0..SCREEN_WIDTH * 5).filter(|&idx| lax.get_display()[idx]).collect();
+        let expected = [2, 3, 4].map(|row| row * SCREEN_WIDTH);
+        assert_eq!(lit, vec![
+            expected[0], expected[0] + 1, expected[0] + 2, expected[0] + 3,
+            expected[1], expected[1] + 3,
+            expected[2], expected[2] + 3,
+        ]);
+    }
+
+    #[test]
+    fn fractional_budget_runs_in_slow_motion() {
+        // jump to itself
+        let mut emulator = emulator_with(&[0x1200]);
+        emulator.set_instruction_budget(0.5);
+        emulator.delay_timer = 20;
+
+        let ran: Vec<usize> = (0..10).map(|_| emulator.run_budgeted_frame().unwrap()).collect();
+        assert_eq!(ran, vec![0, 1, 0, 1, 0, 1, 0, 1, 0, 1]);
+        assert_eq!(ran.iter().sum::<usize>(), 5);
+        // the timers still tick every frame, even the ones that ran nothing
+        assert_eq!(emulator.delay_timer, 10);
+        assert_eq!(emulator.elapsed_instructions_this_frame(), 0);
+    }
+
+    #[test]
+    fn non_finite_budgets_run_nothing() {
+        let mut emulator = emulator_with(&[0x1200]);
+
+        for budget in [f64::INFINITY, f64::NEG_INFINITY, f64::NAN] {
+            emulator.set_instruction_budget(budget);
+            assert_eq!(emulator.run_budgeted_frame().unwrap(), 0);
+        }
+    }
+
+    #[test]
+    fn oversized_roms_are_refused_by_load_and_load_clean() {
+        let mut emulator = emulator_with(&[0x6001]);
+        let rom = vec![0xAA; RAM_SIZE - START_ADDR as usize + 1];
+
+        assert!(matches!(emulator.load(&rom), Err(EmulatorError::RomTooLarge { size: 3585, max: 3584 })));
+        assert!(matches!(emulator.load_clean(&rom), Err(EmulatorError::RomTooLarge { .. })));
+        assert_eq!(&emulator.ram[START_ADDR as usize..START_ADDR as usize + 3], &[0x60, 0x01, 0x00]);
+    }
+
+    #[test]
+    fn errors_display_in_hex() {
+        let mut emulator = emulator_with(&[0x00E0, 0x00E0, 0x8FF9]);
+        run(&mut emulator, 2);
+
+        assert_eq!(emulator.tick().unwrap_err().to_string(), "unknown opcode 0x8FF9 at PC 0x0204");
+    }
+
+    #[cfg(feature = "assembler")]
+    #[test]
+    fn assembled_program_runs() {
+        let source = "
+            :const step 2
+            : main
+                v0 := 0
+            : loop
+                v0 += step
+                if v0 != 6 then jump loop
+                v3 := v0
+                i := data
+                load v1
+            : halt
+                jump halt
+            : data
+                0x12 0x34
+        ";
+        let rom = assembler::assemble(source).unwrap();
+        let mut emulator = Emulator::new();
+        emulator.load(&rom).unwrap();
+        run(&mut emulator, 30);
+
+        assert_eq!(emulator.v_register(3), Some(6));
+        assert_eq!((emulator.v_register(0), emulator.v_register(1)), (Some(0x12), Some(0x34)));
+        assert!(emulator.is_halted());
+    }
+
+    #[cfg(feature = "assembler")]
+    #[test]
+    fn assembler_reports_the_line_of_an_unknown_label() {
+        let error = assembler::assemble("v0 := 1\njump nowhere").unwrap_err();
+
+        assert_eq!(error.line, 2);
+    }
+
+    #[test]
+    fn jump_to_itself_is_a_halt() {
+        // v0 = 1, then jump to 0x202 forever
+        let mut emulator = emulator_with(&[0x6001, 0x1202]);
+        assert!(!emulator.is_halted());
+
+        run(&mut emulator, 1);
+        assert!(emulator.is_halted());
+
+        run(&mut emulator, 1);
+        assert!(emulator.is_halted());
+        assert_eq!(emulator.pc(), 0x202);
+    }
+
+    #[test]
+    fn load_and_reset_keeps_quirks_and_seed() {
+        // v0 = random, then the registers are whatever the new rom leaves them at
+        let quirks = Quirks { shift_vy: true, wrap_x: false, ..Quirks::default() };
+        let mut emulator = Emulator::with_seed(3);
+        emulator.set_quirks(quirks);
+        emulator.load(&rom_bytes(&[0x6005, 0x7001])).unwrap();
+        run(&mut emulator, 2);
+
+        emulator.load_and_reset(&rom_bytes(&[0xC0FF])).unwrap();
+        assert_eq!(emulator.pc(), START_ADDR);
+        assert_eq!(emulator.v_register(0), Some(0));
+        assert_eq!(emulator.quirks(), &quirks);
+
+        let mut fresh = Emulator::with_seed(3);
+        fresh.load(&rom_bytes(&[0xC0FF])).unwrap();
+        run(&mut emulator, 1);
+        run(&mut fresh, 1);
+        assert_eq!(emulator.v_register(0), fresh.v_register(0));
+    }
+
+    #[test]
+    fn hex_string_loads_and_runs() {
+        // v0 = 5, draw the 0 glyph at (5, 0)
+        let mut emulator = Emulator::new();
+        assert_eq!(emulator.load_hex_string("6005 D015").unwrap(), 4);
+        run(&mut emulator, 2);
+
+        assert!(emulator.get_display()[5..9].iter().all(|&pixel| pixel));
+    }
+
+    #[test]
+    fn hex_string_names_the_bad_token() {
+        let mut emulator = Emulator::new();
+        let error = emulator.load_hex_string("6005 D01").unwrap_err();
+
+        assert!(matches!(&error, EmulatorError::InvalidHexToken { token } if token == "D01"));
+        assert!(error.to_string().contains("'D01'"));
+    }
+
+    #[test]
+    fn decode_pulls_out_operands() {
+        assert_eq!(decode(0x00E0), Instruction::ClearScreen);
+        assert_eq!(decode(0x1234), Instruction::Jump { nnn: 0x234 });
+        assert_eq!(decode(0x3A7F), Instruction::SkipIfEq { x: 0xA, nn: 0x7F });
+        assert_eq!(decode(0x8126), Instruction::ShiftRight { x: 1, y: 2 });
+        assert_eq!(decode(0xD12F), Instruction::Draw { x: 1, y: 2, n: 0xF });
+        assert_eq!(decode(0xF533), Instruction::Bcd { x: 5 });
+        assert_eq!(decode(0x8FF9), Instruction::Unknown { opcode: 0x8FF9 });
+    }
+
+    #[test]
+    fn key_wait_takes_the_most_recent_press() {
+        let mut emulator = emulator_with(&[0xF00A]);
+        emulator.keypress(5, true).unwrap();
+        emulator.keypress(2, true).unwrap();
+        run(&mut emulator, 1);
+
+        assert_eq!(emulator.v_register(0), Some(2));
+        assert_eq!(emulator.pc(), START_ADDR + 2);
+    }
+
+    #[test]
+    fn key_wait_falls_back_to_the_key_still_held() {
+        // key 2 was the newest, but it's let go before FX0A runs
+        let mut emulator = emulator_with(&[0xF00A]);
+        emulator.keypress(5, true).unwrap();
+        emulator.keypress(2, true).unwrap();
+        emulator.keypress(2, false).unwrap();
+        run(&mut emulator, 1);
+
+        assert_eq!(emulator.v_register(0), Some(5));
+    }
+
+    #[test]
+    fn ram_usage_follows_the_loaded_rom() {
+        let mut emulator = Emulator::new();
+        emulator.load(&[0; 100]).unwrap();
+
+        assert_eq!(emulator.rom_len(), 100);
+        assert_eq!(emulator.total_ram(), 4096);
+        assert_eq!(emulator.free_ram(), 4096 - 0x200 - 100);
+
+        emulator.reset();
+        assert_eq!(emulator.rom_len(), 0);
+        assert_eq!(emulator.free_ram(), 4096 - 0x200);
+    }
+
+    // lit pixels after drawing an 8x2 block at (62, 31), so it straddles the bottom right corner
+    fn corner_sprite(wrap_x: bool, wrap_y: bool) -> Vec<(usize, usize)> {
+        // I = the two FF rows after the code, v0 = 62, v1 = 31, draw 2 rows
+        let mut emulator = emulator_with(&[0xA208, 0x603E, 0x611F, 0xD012, 0xFFFF]);
+        emulator.set_quirks(Quirks { wrap_x, wrap_y, ..Quirks::default() });
+        run(&mut emulator, 4);
+
+        let display = emulator.get_display();
+        (0..SCREEN_WIDTH * SCREEN_HEIGHT)
+            .filter(|&idx| display[idx])
+            .map(|idx| (idx % SCREEN_WIDTH, idx / SCREEN_WIDTH))
+            .collect()
+    }
+
+    #[test]
+    fn sprite_wraps_on_both_axes() {
+        let lit = corner_sprite(true, true);
+
+        assert_eq!(lit.len(), 16);
+        assert!(lit.contains(&(0, 0)) && lit.contains(&(63, 31)));
+    }
+
+    #[test]
+    fn sprite_clips_x_and_wraps_y() {
+        assert_eq!(corner_sprite(false, true), vec![(62, 0), (63, 0), (62, 31), (63, 31)]);
+    }
+
+    #[test]
+    fn sprite_wraps_x_and_clips_y() {
+        let lit = corner_sprite(true, false);
+
+        assert_eq!(lit, [0, 1, 2, 3, 4, 5, 62, 63].map(|x| (x, 31)).to_vec());
+    }
+
+    #[test]
+    fn sprite_clips_on_both_axes() {
+        assert_eq!(corner_sprite(false, false), vec![(62, 31), (63, 31)]);
+    }
+
+    #[test]
+    fn tall_sprite_past_the_end_of_ram_follows_the_memory_policy() {
+        // I = 4090 (FFA), draw 10 rows, the last at 4099
+        let rom = [0xAFFA, 0xD00A];
+        let mut strict = emulator_with(&rom);
+        run(&mut strict, 1);
+        assert!(matches!(strict.tick(), Err(EmulatorError::MemoryOutOfBounds { address: 4099 })));
+
+        let mut lax = emulator_with(&rom);
+        lax.set_strict(false);
+        run(&mut lax, 2);
+        assert_eq!(lax.pc(), START_ADDR + 4);
+    }
+
+    #[test]
+    fn sprites_can_be_drawn_from_the_fontset() {
+        // I = 0x05, the 1 glyph's 20 60 20 20 70
+        let mut emulator = emulator_with(&[0xA005, 0xD005]);
+        run(&mut emulator, 2);
+
+        let rows: Vec<u8> = (0..5)
+            .map(|row| (0..8).fold(0, |byte, col| byte << 1 | emulator.get_display()[row * SCREEN_WIDTH + col] as u8))
+            .collect();
+        assert_eq!(rows, vec![0x20, 0x60, 0x20, 0x20, 0x70]);
+    }
+
+    #[test]
+    fn zero_glyph_renders_as_ascii() {
+        // I = the 0 glyph, draw it at (0, 0)
+        let mut emulator = emulator_with(&[0xA000, 0xD005]);
+        run(&mut emulator, 2);
+
+        let ascii = emulator.display_to_ascii();
+        let glyph: Vec<&str> = ascii.lines().take(6).map(|line| &line[..6]).collect();
+        assert_eq!(glyph, vec!["####..", "#..#..", "#..#..", "#..#..", "####..", "......"]);
+        assert_eq!(ascii.lines().count(), SCREEN_HEIGHT);
+        assert!(ascii.lines().all(|line| line.len() == SCREEN_WIDTH));
+    }
+
+    #[test]
+    fn reset_keys_releases_every_key() {
+        let mut emulator = Emulator::new();
+        for key in [0x1, 0x5, 0xF] {
+            emulator.keypress(key, true).unwrap();
+        }
+        assert_eq!(emulator.keys_mask(), 0b1000_0000_0010_0010);
+
+        emulator.reset_keys();
+        assert_eq!(emulator.keys_mask(), 0);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn rom_path_loads_a_file() {
+        let path = std::env::temp_dir().join(format!("chip8_core_test_{}.ch8", std::process::id()));
+        std::fs::write(&path, rom_bytes(&[0x6007])).unwrap();
+
+        let mut emulator = Emulator::new();
+        let loaded = emulator.load_rom_path(&path);
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(loaded.unwrap(), 2);
+        assert_eq!(&emulator.ram[START_ADDR as usize..START_ADDR as usize + 3], &[0x60, 0x07, 0x00]);
+
+        run(&mut emulator, 1);
+        assert_eq!(emulator.v_register(0), Some(7));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn slot_path_fills_a_slot_from_a_file() {
+        let path = std::env::temp_dir().join(format!("chip8_core_slot_test_{}.ch8", std::process::id()));
+        std::fs::write(&path, rom_bytes(&[0x6009, 0x1202])).unwrap();
+
+        let mut emulator = Emulator::new();
+        let loaded = emulator.load_slot_path(3, &path);
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(loaded.unwrap(), 4);
+
+        emulator.activate_slot(3).unwrap();
+        run(&mut emulator, 1);
+        assert_eq!(emulator.v_register(0), Some(9));
+        assert!(matches!(emulator.load_slot_path(4, &path), Err(EmulatorError::Io { .. })));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn missing_rom_path_is_an_io_error() {
+        let path = std::env::temp_dir().join("chip8_core_test_missing.ch8");
+
+        assert!(matches!(Emulator::new().load_rom_path(path), Err(EmulatorError::Io { .. })));
+    }
+
+    // a load_pack container around font and rom
+    fn pack(font: &[u8], rom: &[u8]) -> Vec<u8> {
+        let mut data = PACK_MAGIC.to_vec();
+        data.extend_from_slice(&(font.len() as u16).to_be_bytes());
+        data.extend_from_slice(&(rom.len() as u16).to_be_bytes());
+        data.extend_from_slice(font);
+        data.extend_from_slice(rom);
+
+        data
+    }
+
+    #[test]
+    fn pack_font_is_used_by_fx29() {
+        // every font byte is its own address, so the 3 glyph's top row is 0x0F
+        let font: Vec<u8> = (0..FONTSET_SIZE as u8).collect();
+        // v0 = 3, I = glyph of v0, draw its top row at (v1, v1) = (0, 0)
+        let rom = rom_bytes(&[0x6003, 0xF029, 0xD111]);
+        let mut emulator = Emulator::new();
+        emulator.load_pack(&pack(&font, &rom)).unwrap();
+        run(&mut emulator, 3);
+
+        assert_eq!(emulator.i_register(), 15);
+        let top_row: Vec<bool> = emulator.get_display()[..8].to_vec();
+        assert_eq!(top_row, vec![false, false, false, false, true, true, true, true]);
+    }
+
+    #[test]
+    fn bad_packs_change_nothing() {
+        let font = [0xFF; FONTSET_SIZE];
+        let rom = rom_bytes(&[0x6003]);
+        let mut emulator = Emulator::new();
+
+        let mut wrong_magic = pack(&font, &rom);
+        wrong_magic[0] = b'X';
+        assert!(matches!(emulator.load_pack(&wrong_magic), Err(EmulatorError::InvalidPack { .. })));
+
+        let mut truncated = pack(&font, &rom);
+        truncated.pop();
+        assert!(matches!(emulator.load_pack(&truncated), Err(EmulatorError::InvalidPack { .. })));
+
+        assert!(matches!(emulator.load_pack(&pack(&font[..40], &rom)), Err(EmulatorError::InvalidFontset { len: 40, .. })));
+        assert_eq!(&emulator.ram[..FONTSET_SIZE], &FONTSET[..]);
+        assert_eq!(emulator.rom_len(), 0);
+    }
+
+    #[test]
+    fn step_back_frame_restores_the_previous_boundary() {
+        let mut emulator = emulator_with(&[0x7001; 8]);
+        emulator.set_frame_rewind(true);
+        assert!(!emulator.step_back_frame());
+
+        // first frame ends with v0 = 2, the second with v0 = 5
+        run(&mut emulator, 2);
+        emulator.update_timers();
+        run(&mut emulator, 3);
+        emulator.update_timers();
+
+        assert!(emulator.step_back_frame());
+        assert_eq!(emulator.v_register(0), Some(2));
+        assert_eq!(emulator.pc(), START_ADDR + 4);
+        assert!(!emulator.step_back_frame());
+    }
+
+    #[test]
+    fn step_back_part_way_through_a_frame_goes_to_its_start() {
+        let mut emulator = emulator_with(&[0x7001; 8]);
+        emulator.set_frame_rewind(true);
+        run(&mut emulator, 2);
+        emulator.update_timers();
+        run(&mut emulator, 1);
+
+        assert!(emulator.step_back_frame());
+        assert_eq!(emulator.v_register(0), Some(2));
+    }
+
+    #[test]
+    fn set_v_register_feeds_opcodes() {
+        // vA = v5
+        let mut emulator = emulator_with(&[0x8A50]);
+        emulator.set_v_register(5, 0x42).unwrap();
+        run(&mut emulator, 1);
+
+        assert_eq!(emulator.v_register(0xA), Some(0x42));
+    }
+
+    #[test]
+    fn set_v_register_rejects_v16() {
+        let mut emulator = Emulator::new();
+
+        assert!(matches!(emulator.set_v_register(16, 1), Err(EmulatorError::InvalidRegisterIndex { index: 16 })));
+    }
+
+    // an rng that always rolls 0xAB
+    struct ConstantRng;
+
+    impl RngCore for ConstantRng {
+        fn next_u32(&mut self) -> u32 {
+            0xAB
+        }
+
+        fn next_u64(&mut self) -> u64 {
+            0xAB
+        }
+
+        fn fill_bytes(&mut self, dest: &mut [u8]) {
+            dest.fill(0xAB);
+        }
+
+        fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+            self.fill_bytes(dest);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn user_rng_makes_cxnn_deterministic() {
+        // v0 = random & FF, v1 = random & 0F
+        let mut emulator = emulator_with(&[0xC0FF, 0xC10F]);
+        emulator.set_rng(Box::new(ConstantRng));
+        run(&mut emulator, 2);
+        assert_eq!((emulator.v_register(0), emulator.v_register(1)), (Some(0xAB), Some(0x0B)));
+
+        // kept across resets
+        emulator.load_and_reset(&rom_bytes(&[0xC2F0])).unwrap();
+        run(&mut emulator, 1);
+        assert_eq!(emulator.v_register(2), Some(0xA0));
+    }
+
+    #[cfg(feature = "base64")]
+    #[test]
+    fn base64_rom_round_trips() {
+        use base64::Engine;
+
+        let rom = rom_bytes(&[0x00E0, 0xA22A, 0x600C, 0xD01F]);
+        let encoded = base64::engine::general_purpose::STANDARD.encode(&rom);
+        // pasted text is often wrapped over several lines
+        let (first, second) = encoded.split_at(7);
+        let mut emulator = Emulator::new();
+
+        assert_eq!(emulator.load_base64(&format!("{}\n  {}", first, second)).unwrap(), rom.len());
+        assert_eq!(&emulator.ram[START_ADDR as usize..START_ADDR as usize + rom.len()], &rom[..]);
+    }
+
+    #[cfg(feature = "base64")]
+    #[test]
+    fn bad_base64_is_a_decode_error() {
+        assert!(matches!(Emulator::new().load_base64("not base64!"), Err(EmulatorError::DecodeError { .. })));
+    }
+
+    #[test]
+    fn keypad_layout_is_the_cosmac_vip_one() {
+        assert_eq!(keypad_layout(), [
+            [0x1, 0x2, 0x3, 0xC],
+            [0x4, 0x5, 0x6, 0xD],
+            [0x7, 0x8, 0x9, 0xE],
+            [0xA, 0x0, 0xB, 0xF],
+        ]);
+    }
+
+    #[test]
+    fn keypad_position_finds_every_key() {
+        for key in 0..NUM_KEYS as u8 {
+            let (row, column) = keypad_position(key).unwrap();
+            assert_eq!(keypad_layout()[row][column], key);
+        }
+        assert_eq!(keypad_position(0x10), None);
+    }
+
+    // lit columns of the top row of one plane
+    fn lit_top_row(plane: &[bool]) -> Vec<usize> {
+        (0..8).filter(|&col| plane[col]).collect()
+    }
+
+    #[test]
+    fn clear_only_touches_the_selected_planes() {
+        // select both planes, draw one row into each (F0 into the first, 0F into plane2),
+        // then select the first plane alone and clear it
+        let mut emulator = emulator_with(&[0xF301, 0xA20A, 0xD011, 0xF101, 0x00E0, 0xF00F]);
+        run(&mut emulator, 3);
+        assert_eq!(lit_top_row(emulator.get_display()), vec![0, 1, 2, 3]);
+        assert_eq!(lit_top_row(emulator.plane2()), vec![4, 5, 6, 7]);
+
+        run(&mut emulator, 2);
+        assert_eq!(emulator.plane_mask(), 0b01);
+        assert!(!emulator.get_display().contains(&true));
+        assert_eq!(lit_top_row(emulator.plane2()), vec![4, 5, 6, 7]);
+    }
+
+    #[test]
+    fn no_selected_plane_draws_and_clears_nothing() {
+        // draw the 0 glyph, select no plane, then clear and draw again
+        let mut emulator = emulator_with(&[0xD015, 0xF001, 0x00E0, 0xD015]);
+        run(&mut emulator, 4);
+
+        assert_eq!(lit_top_row(emulator.get_display()), vec![0, 1, 2, 3]);
+        assert!(!emulator.plane2().contains(&true));
+    }
+
+    #[test]
+    fn plane_values_pick_palette_entries() {
+        // select both planes, draw F0 into the first and 3C into plane2, then halt
+        let mut emulator = emulator_with(&[0xF301, 0xA208, 0xD011, 0x1206, 0xF03C]);
+        run(&mut emulator, 3);
+
+        // columns 2 and 3 are on in both planes
+        let palette = [[0; 4], [1; 4], [2; 4], [3; 4]];
+        let buffer = emulator.frame_buffer_rgba_palette(palette);
+        let values: Vec<u8> = (0..8).map(|col| buffer[col * 4]).collect();
+        assert_eq!(values, vec![1, 1, 3, 3, 2, 2, 0, 0]);
+    }
+
+    #[test]
+    fn register_store_past_the_end_of_ram_wraps_or_errors() {
+        // v0 = 1, v1 = 2, v2 = 3, I = 0xFFE, store v0..=v2
+        let program = [0x6001, 0x6102, 0x6203, 0xAFFE, 0xF255];
+
+        let mut wrap = emulator_with(&program);
+        wrap.set_memory_policy(MemoryPolicy::Wrap);
+        run(&mut wrap, 5);
+        assert_eq!(wrap.memory_dump(0xFFE, 2), vec![1, 2]);
+        assert_eq!(wrap.memory_dump(0, 1), vec![3]);
+
+        let mut error = emulator_with(&program);
+        error.set_memory_policy(MemoryPolicy::Error);
+        run(&mut error, 4);
+        assert!(matches!(error.tick(), Err(EmulatorError::MemoryOutOfBounds { address: 0x1000 })));
+        assert_eq!(error.memory_dump(0xFFE, 2), vec![0, 0]);
+        assert_eq!(error.memory_dump(0, 1), vec![FONTSET[0]]);
+    }
+
+    #[test]
+    fn register_load_past_the_end_of_ram_wraps_or_errors() {
+        // I = 0xFFE, load v0..=v2
+        let program = [0xAFFE, 0xF265];
+
+        let mut wrap = emulator_with(&program);
+        wrap.set_memory_policy(MemoryPolicy::Wrap);
+        wrap.ram[0xFFE] = 7;
+        wrap.ram[0xFFF] = 8;
+        run(&mut wrap, 2);
+        assert_eq!(&wrap.v_registers[..3], &[7, 8, FONTSET[0]]);
+
+        let mut error = emulator_with(&program);
+        error.set_memory_policy(MemoryPolicy::Error);
+        error.ram[0xFFE] = 7;
+        run(&mut error, 1);
+        assert!(matches!(error.tick(), Err(EmulatorError::MemoryOutOfBounds { address: 0x1000 })));
+        assert_eq!(&error.v_registers[..3], &[0, 0, 0]);
+    }
+
+    #[test]
+    fn start_address_moves_loading_and_execution() {
+        let mut emulator = Emulator::new();
+        emulator.set_start_addr(0x600).unwrap();
+        assert_eq!(emulator.pc(), 0x600);
+
+        // still there after a reset, and load puts the rom there instead of at START_ADDR
+        emulator.reset();
+        assert_eq!(emulator.pc(), 0x600);
+        emulator.load(&rom_bytes(&[0x6042])).unwrap();
+        assert_eq!(emulator.memory_dump(0x600, 2), vec![0x60, 0x42]);
+        assert_eq!(emulator.memory_dump(START_ADDR, 2), vec![0, 0]);
+
+        emulator.tick().unwrap();
+        assert_eq!(emulator.v_registers[0], 0x42);
+        assert_eq!(emulator.pc(), 0x602);
+    }
+
+    #[test]
+    fn odd_or_out_of_range_start_addresses_are_refused() {
+        let mut emulator = Emulator::new();
+
+        assert!(matches!(emulator.set_start_addr(0x601), Err(EmulatorError::MisalignedPc { pc: 0x601 })));
+        assert!(matches!(emulator.set_start_addr(0xFFF), Err(EmulatorError::MisalignedPc { .. })));
+        assert!(matches!(emulator.set_start_addr(0x1000), Err(EmulatorError::PcOutOfBounds { pc: 0x1000 })));
+        assert_eq!(emulator.pc(), START_ADDR);
+    }
+
+    #[test]
+    fn fetch_endianness_picks_the_byte_order() {
+        // 00E0 in the byte order each setting expects
+        for (endianness, bytes) in [(Endianness::Big, [0x00, 0xE0]), (Endianness::Little, [0xE0, 0x00])] {
+            let mut emulator = Emulator::new();
+            emulator.set_fetch_endianness(endianness);
+            emulator.load(&bytes).unwrap();
+            emulator.screen[0] = true;
+
+            emulator.tick().unwrap();
+            assert!(!emulator.get_display().contains(&true));
+        }
+
+        // read the wrong way round it's E000, which doesn't exist
+        let mut emulator = Emulator::new();
+        emulator.load(&[0xE0, 0x00]).unwrap();
+        assert!(matches!(emulator.tick(), Err(EmulatorError::UnknownOpcode { opcode: 0xE000, .. })));
+    }
+
+    #[test]
+    fn strict_alignment_refuses_odd_jumps() {
+        // jump to 0x203, where the bytes 60 2A set v0
+        let rom = [0x12, 0x03, 0x00, 0x60, 0x2A];
+
+        let mut strict = Emulator::new();
+        strict.set_strict_alignment(true);
+        strict.load(&rom).unwrap();
+        strict.tick().unwrap();
+        assert!(matches!(strict.tick(), Err(EmulatorError::MisalignedPc { pc: 0x203 })));
+
+        let mut lax = Emulator::new();
+        lax.set_strict_alignment(false);
+        lax.load(&rom).unwrap();
+        run(&mut lax, 2);
+        assert_eq!(lax.v_registers[0], 0x2A);
+        assert_eq!(lax.pc(), 0x205);
+    }
+
+    #[test]
+    fn run_to_completion_stops_at_the_self_jump() {
+        // v0 = 1, add 1 twice, then spin at 0x206
+        let mut emulator = emulator_with(&[0x6001, 0x7001, 0x7001, 0x1206]);
+
+        assert_eq!(emulator.run_rom_to_completion(1000).unwrap(), 3);
+        assert_eq!(emulator.v_registers[0], 3);
+        assert!(emulator.is_halted());
+    }
+
+    #[test]
+    fn run_to_completion_gives_up_at_max_cycles() {
+        // add 1 to v0 forever
+        let mut emulator = emulator_with(&[0x7001, 0x1200]);
+        emulator.delay_timer = 10;
+
+        assert_eq!(emulator.run_rom_to_completion(25).unwrap(), 25);
+        assert_eq!(emulator.v_registers[0], 13);
+        // one timer tick every CYCLES_PER_FRAME
+        assert_eq!(emulator.delay_timer, 8);
+    }
+
+    #[test]
+    fn or_mode_keeps_pixels_that_xor_would_erase() {
+        // draw the top row of the "0" glyph at 0,0 twice
+        let program = [0xA000, 0xD001, 0xD001];
+
+        let mut xor = emulator_with(&program);
+        xor.set_draw_mode(DrawMode::Xor);
+        run(&mut xor, 3);
+        assert!(lit_top_row(xor.get_display()).is_empty());
+        assert_eq!(xor.v_registers[0xF], 1);
+
+        let mut or = emulator_with(&program);
+        or.set_draw_mode(DrawMode::Or);
+        run(&mut or, 3);
+        assert_eq!(lit_top_row(or.get_display()), vec![0, 1, 2, 3]);
+        assert_eq!(or.v_registers[0xF], 0);
+    }
+
+    #[test]
+    fn rpl_flags_round_trip_across_a_reset() {
+        // FF75 saves v0..=v7, the flags stop at 8
+        let mut emulator = emulator_with(&[0xFF75]);
+        fill_registers(&mut emulator);
+        emulator.tick().unwrap();
+        assert_eq!(emulator.rpl_flags(), [0x10, 0x11, 0x12, 0x13, 0x14, 0x15, 0x16, 0x17]);
+
+        emulator.reset();
+        assert_eq!(emulator.rpl_flags(), [0x10, 0x11, 0x12, 0x13, 0x14, 0x15, 0x16, 0x17]);
+
+        // FF85 fills v0..=v7 and leaves the rest alone
+        emulator.load(&rom_bytes(&[0xFF85])).unwrap();
+        emulator.tick().unwrap();
+        assert_eq!(&emulator.v_registers[..8], &[0x10, 0x11, 0x12, 0x13, 0x14, 0x15, 0x16, 0x17]);
+        assert_eq!(&emulator.v_registers[8..], &[0; 8]);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn zero_max_ips_means_uncapped() {
+        let mut emulator = emulator_with(&[0x7001, 0x1200]);
+        emulator.set_max_ips(Some(1));
+        emulator.set_max_ips(Some(0));
+        assert_eq!(emulator.max_ips, None);
+
+        // at 1 instruction a second this would take over a minute
+        let start = Instant::now();
+        assert_eq!(emulator.run_rom_to_completion(100).unwrap(), 100);
+        assert!(start.elapsed() < Duration::from_secs(10));
+    }
+
+    #[test]
+    fn frames_round_trip_through_encode_and_decode() {
+        // the "0" glyph at 0,0
+        let mut emulator = emulator_with(&[0xA000, 0xD005]);
+        run(&mut emulator, 2);
+
+        let frame = emulator.encode_frame();
+        assert_eq!(frame.len(), FRAME_HEADER_SIZE + SCREEN_WIDTH * SCREEN_HEIGHT / 8);
+        assert_eq!(&frame[..FRAME_HEADER_SIZE], &[FRAME_VERSION, 64, 32, 1]);
+        // the first byte of the first two rows
+        assert_eq!((frame[FRAME_HEADER_SIZE], frame[FRAME_HEADER_SIZE + 8]), (0xF0, 0x90));
+
+        let mut viewer = Emulator::new();
+        viewer.decode_frame(&frame).unwrap();
+        assert_eq!(viewer.get_display(), emulator.get_display());
+    }
+
+    #[test]
+    fn bad_frames_are_refused_and_change_nothing() {
+        let mut emulator = emulator_with(&[0xA000, 0xD005]);
+        run(&mut emulator, 2);
+        let frame = emulator.encode_frame();
+
+        let mut wrong_version = frame.clone();
+        wrong_version[0] = FRAME_VERSION + 1;
+        let mut wrong_size = frame.clone();
+        wrong_size[1] = 128;
+        let truncated = &frame[..frame.len() - 1];
+
+        let mut viewer = Emulator::new();
+        for (bytes, expected) in [
+            (&wrong_version[..], "unknown version"),
+            (&wrong_size[..], "only 64x32 frames with one plane are supported"),
+            (truncated, "pixel data doesn't match the header"),
+            (&frame[..2], "too short for the header"),
+        ] {
+            assert!(matches!(viewer.decode_frame(bytes), Err(EmulatorError::InvalidFrame { reason }) if reason == expected));
+        }
+        assert!(!viewer.get_display().contains(&true));
+    }
+
+    #[test]
+    fn single_cycle_reports_a_carrying_add() {
+        // v0 = 0xFF, v1 = 2, v0 += v1
+        let mut emulator = emulator_with(&[0x60FF, 0x6102, 0x8014]);
+        run(&mut emulator, 2);
+
+        let report = emulator.single_cycle().unwrap();
+        assert_eq!(report, CycleReport {
+            pc: 0x204,
+            opcode: 0x8014,
+            instruction: Instruction::AddReg { x: 0, y: 1 },
+            changed_registers: vec![0, 0xF],
+            changed_memory: vec![],
+            i_changed: false,
+            screen_changed: false,
+        });
+        assert_eq!((emulator.v_registers[0], emulator.v_registers[0xF]), (1, 1));
+    }
+
+    #[test]
+    fn self_modified_code_is_reported_once() {
+        // write 6042 over the 0000 at 0x20A, run it, then loop back onto it
+        let program = [0x6060, 0x6142, 0xA20A, 0xF155, 0x120A, 0x0000, 0x120A];
+
+        let mut detecting = emulator_with(&program);
+        detecting.set_detect_smc(true);
+        run(&mut detecting, 8);
+        assert_eq!(detecting.v_registers[0], 0x42);
+        assert_eq!(detecting.take_smc_events(), vec![0x20A]);
+        assert!(detecting.take_smc_events().is_empty());
+
+        let mut ignoring = emulator_with(&program);
+        run(&mut ignoring, 8);
+        assert_eq!(ignoring.v_registers[0], 0x42);
+        assert!(ignoring.take_smc_events().is_empty());
+    }
+
+    #[test]
+    fn watched_writes_are_reported_with_their_cycle() {
+        // v0 = 123, I = 0x300, bcd, then v0 = 7, v1 = 8 and store both
+        let mut emulator = emulator_with(&[0x607B, 0xA300, 0xF033, 0x6007, 0x6108, 0xF155]);
+        emulator.watch_addr(0x300);
+        emulator.watch_addr(0x301);
+        run(&mut emulator, 6);
+
+        assert_eq!(emulator.take_watch_hits(), vec![(3, 0x300, 1), (3, 0x301, 2), (6, 0x300, 7), (6, 0x301, 8)]);
+        assert!(emulator.take_watch_hits().is_empty());
+    }
+
+    #[test]
+    fn cheats_override_rom_writes_until_cleared() {
+        // v0 = 0x11, I = 0x300, store v0 twice
+        let mut emulator = emulator_with(&[0x6011, 0xA300, 0xF055, 0xF055]);
+        emulator.add_cheat(0x300, 0x99);
+        run(&mut emulator, 3);
+        assert_eq!(emulator.memory_dump(0x300, 1), vec![0x99]);
+
+        emulator.clear_cheats();
+        emulator.tick().unwrap();
+        assert_eq!(emulator.memory_dump(0x300, 1), vec![0x11]);
+    }
+
+    #[test]
+    fn double_buffered_draws_only_show_after_present_frame() {
+        // the top row of the "0" glyph at 0,0
+        let program = [0xA000, 0xD001];
+
+        let mut buffered = emulator_with(&program);
+        buffered.set_double_buffering(true);
+        run(&mut buffered, 2);
+        assert!(lit_top_row(buffered.get_display()).is_empty());
+        buffered.present_frame();
+        assert_eq!(lit_top_row(buffered.get_display()), vec![0, 1, 2, 3]);
+
+        let mut direct = emulator_with(&program);
+        direct.set_double_buffering(false);
+        run(&mut direct, 2);
+        assert_eq!(lit_top_row(direct.get_display()), vec![0, 1, 2, 3]);
+        direct.present_frame();
+        assert_eq!(lit_top_row(direct.get_display()), vec![0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn muted_sound_timer_still_counts_down() {
+        // v0 = 3, sound timer = v0
+        let mut emulator = emulator_with(&[0x6003, 0xF018]);
+        emulator.set_sound_enabled(false);
+        run(&mut emulator, 2);
+        assert_eq!(emulator.sound_timer, 3);
+
+        for remaining in (0..3).rev() {
+            assert!(!emulator.is_beeping());
+            assert_eq!(emulator.beep_duration_remaining(), Duration::ZERO);
+            emulator.update_timers();
+            assert_eq!(emulator.sound_timer, remaining);
+        }
+
+        // turning it back on mid-tone starts beeping straight away
+        emulator.sound_timer = 2;
+        emulator.set_sound_enabled(true);
+        assert!(emulator.is_beeping());
+    }
+
+    #[test]
+    fn every_quirk_can_be_set_by_name() {
+        for (idx, (name, value)) in Quirks::default().named().into_iter().enumerate() {
+            let mut emulator = Emulator::new();
+            emulator.set_quirk(name, !value).unwrap();
+
+            let flipped: Vec<usize> = (0..8).filter(|&other| emulator.quirks().named()[other] != Quirks::default().named()[other]).collect();
+            assert_eq!(flipped, vec![idx], "{}", name);
+        }
+    }
+
+    #[test]
+    fn clip_turns_off_wrapping_on_both_axes() {
+        let mut emulator = Emulator::new();
+
+        emulator.set_quirk("clip", true).unwrap();
+        assert!(!emulator.quirks().wrap_x && !emulator.quirks().wrap_y);
+        emulator.set_quirk("clip", false).unwrap();
+        assert!(emulator.quirks().wrap_x && emulator.quirks().wrap_y);
+    }
+
+    #[test]
+    fn unknown_quirk_names_are_refused() {
+        let mut emulator = Emulator::new();
+
+        assert!(matches!(emulator.set_quirk("shift_vz", true), Err(EmulatorError::UnknownQuirk { name }) if name == "shift_vz"));
+        assert_eq!(*emulator.quirks(), Quirks::default());
+    }
+
+    #[test]
+    fn unwind_steps_out_of_nested_calls() {
+        // three nested calls, each to the next, the innermost spins at 0x20C
+        let mut emulator = emulator_with(&[0x2204, 0x1202, 0x2208, 0x00EE, 0x220C, 0x00EE, 0x120C]);
+        run(&mut emulator, 3);
+        assert_eq!(emulator.call_stack(), &[0x202, 0x206, 0x20A]);
+        assert_eq!(emulator.pc(), 0x20C);
+
+        emulator.unwind(1);
+        assert_eq!(emulator.call_stack(), &[0x202, 0x206]);
+        assert_eq!(emulator.pc(), 0x20A);
+
+        // more frames than there are calls stops at the outermost
+        emulator.unwind(usize::MAX);
+        assert!(emulator.call_stack().is_empty());
+        assert_eq!(emulator.pc(), 0x202);
+    }
+
+    #[test]
+    fn raw_stack_keeps_returned_addresses() {
+        // call 0x204 and return straight away
+        let mut emulator = emulator_with(&[0x2204, 0x1202, 0x00EE]);
+        run(&mut emulator, 2);
+
+        assert_eq!(emulator.stack_pointer(), 0);
+        assert!(emulator.call_stack().is_empty());
+        assert_eq!(emulator.stack_raw()[0], 0x202);
+        assert_eq!(emulator.stack_raw()[1..], [0; STACK_SIZE - 1]);
+    }
+
+    #[test]
+    fn skipped_opcode_only_follows_taken_skips() {
+        // taken skip, plain op, taken skip, untaken skip
+        let mut emulator = emulator_with(&[0x3000, 0x6011, 0x6122, 0x3000, 0x6033, 0x3001, 0x6044]);
+        let mut skipped = Vec::new();
+        for _ in 0..4 {
+            emulator.tick().unwrap();
+            skipped.push(emulator.skipped_opcode());
+        }
+
+        assert_eq!(skipped, vec![Some(0x6011), None, Some(0x6033), None]);
+    }
+
+    #[test]
+    fn last_opcode_follows_ticks_and_resets() {
+        let mut emulator = emulator_with(&[0x6011, 0x7001]);
+        assert_eq!(emulator.last_opcode(), 0);
+
+        emulator.tick().unwrap();
+        assert_eq!(emulator.last_opcode(), 0x6011);
+        emulator.tick().unwrap();
+        assert_eq!(emulator.last_opcode(), 0x7001);
+
+        emulator.reset();
+        assert_eq!(emulator.last_opcode(), 0);
+    }
+
+    #[test]
+    fn last_tick_drew_tracks_display_changes() {
+        // draw, add, clear a lit screen, clear a blank one
+        let mut emulator = emulator_with(&[0xA000, 0xD001, 0x7101, 0x00E0, 0x00E0]);
+        let mut drew = Vec::new();
+        for _ in 0..5 {
+            emulator.tick().unwrap();
+            drew.push(emulator.last_tick_drew());
+        }
+        assert_eq!(drew, vec![false, true, false, true, false]);
+
+        // in or mode drawing over the same lit pixels changes nothing
+        let mut or = emulator_with(&[0xA000, 0xD001, 0xD001]);
+        or.set_draw_mode(DrawMode::Or);
+        run(&mut or, 2);
+        assert!(or.last_tick_drew());
+        or.tick().unwrap();
+        assert!(!or.last_tick_drew());
+    }
+
+    #[test]
+    fn instructions_this_frame_resets_with_the_timers() {
+        let mut emulator = emulator_with(&[0x7001, 0x1200]);
+        run(&mut emulator, 3);
+        assert_eq!(emulator.elapsed_instructions_this_frame(), 3);
+
+        emulator.update_timers();
+        assert_eq!(emulator.elapsed_instructions_this_frame(), 0);
+        emulator.tick().unwrap();
+        assert_eq!(emulator.elapsed_instructions_this_frame(), 1);
+    }
+
+    #[test]
+    fn draw_calls_skip_vblank_retries() {
+        // draw the same row twice, the second waits for the next frame
+        let mut emulator = emulator_with(&[0xA000, 0xD001, 0xD001]);
+        emulator.set_quirk("vblank", true).unwrap();
+        run(&mut emulator, 4);
+        assert_eq!(emulator.draw_calls(), 1);
+        assert_eq!(emulator.pc(), 0x204);
+
+        emulator.update_timers();
+        emulator.tick().unwrap();
+        assert_eq!(emulator.draw_calls(), 2);
+
+        emulator.reset();
+        assert_eq!(emulator.draw_calls(), 0);
+    }
+
+    #[test]
+    fn next_instruction_peeks_without_running() {
+        let mut emulator = emulator_with(&[0x6011, 0x7001]);
+
+        assert_eq!(emulator.next_instruction(), Some((0x6011, "LD V0, 0x11".to_string())));
+        assert_eq!(emulator.next_instruction(), Some((0x6011, "LD V0, 0x11".to_string())));
+        assert_eq!((emulator.pc(), emulator.cycle_count()), (0x200, 0));
+
+        emulator.tick().unwrap();
+        assert_eq!(emulator.next_instruction(), Some((0x7001, "ADD V0, 0x01".to_string())));
+
+        // only one byte left, not a whole opcode
+        emulator.program_counter = 0xFFF;
+        assert_eq!(emulator.next_instruction(), None);
+    }
+
+    #[test]
+    fn disassemble_from_lists_addresses_and_mnemonics() {
+        let emulator = emulator_with(&[0x6011, 0x7001, 0x6122]);
+
+        assert_eq!(emulator.disassemble_from(0x200, 3), vec![
+            (0x200, "LD V0, 0x11".to_string()),
+            (0x202, "ADD V0, 0x01".to_string()),
+            (0x204, "LD V1, 0x22".to_string()),
+        ]);
+    }
+
+    #[test]
+    fn disassemble_from_stops_at_the_end_of_ram() {
+        let emulator = Emulator::new();
+
+        let addresses: Vec<u16> = emulator.disassemble_from(0xFFA, 5).into_iter().map(|(address, _)| address).collect();
+        assert_eq!(addresses, vec![0xFFA, 0xFFC, 0xFFE]);
+        // an odd start leaves a single byte at the end
+        assert_eq!(emulator.disassemble_from(0xFFB, 5).len(), 2);
+        assert!(emulator.disassemble_from(0xFFFF, 5).is_empty());
+    }
+
+    #[test]
+    fn referenced_keys_follow_set_then_skip_pairs() {
+        let emulator = Emulator::new();
+        let rom = rom_bytes(&[
+            0x630A, 0xE39E, // key A
+            0x610F, 0xE1A1, // key F
+            0x6205, 0xE19E, // different register, unknown key
+            0x6020, 0xE09E, // 0x20 isn't a key
+        ]);
+
+        assert_eq!(emulator.referenced_keys(&rom), HashSet::from([0xA, 0xF]));
+        assert!(emulator.referenced_keys(&rom_bytes(&[0x6001, 0x7001, 0xF00A])).is_empty());
+    }
+
+    #[test]
+    fn rom_checksum_is_fnv1a_of_the_loaded_rom() {
+        let mut emulator = Emulator::new();
+        assert_eq!(emulator.rom_checksum(), None);
+
+        // the published 64 bit FNV-1a test vectors
+        emulator.load(b"a").unwrap();
+        assert_eq!(emulator.rom_checksum(), Some(0xAF63DC4C8601EC8C));
+        emulator.load(b"foobar").unwrap();
+        assert_eq!(emulator.rom_checksum(), Some(0x85944171F73967E8));
+    }
+
+    #[test]
+    fn load_clean_zeroes_what_a_bigger_rom_left() {
+        let mut emulator = Emulator::new();
+        emulator.load_clean(&[0xAA; 16]).unwrap();
+        emulator.load_clean(&[0x60, 0x01]).unwrap();
+
+        assert_eq!(emulator.memory_dump(START_ADDR, 2), vec![0x60, 0x01]);
+        assert_eq!(emulator.memory_dump(START_ADDR + 2, 14), vec![0; 14]);
+
+        // plain load leaves the tail behind
+        emulator.load(&[0xAA; 16]).unwrap();
+        emulator.load(&[0x60, 0x01]).unwrap();
+        assert_eq!(emulator.memory_dump(START_ADDR + 2, 1), vec![0xAA]);
+    }
+
+    #[test]
+    fn ram_images_round_trip() {
+        // v0 = 0x42, I = 0x300, store v0
+        let mut source = emulator_with(&[0x6042, 0xA300, 0xF055]);
+        run(&mut source, 3);
+        let image = source.ram_image();
+
+        let mut copy = Emulator::new();
+        copy.set_ram_image(&image).unwrap();
+        assert_eq!(copy.ram_image(), image);
+        assert_eq!(copy.memory_dump(0x300, 1), vec![0x42]);
+    }
+
+    #[test]
+    fn ram_images_of_the_wrong_size_are_refused() {
+        let mut emulator = emulator_with(&[0x6042]);
+
+        assert!(matches!(
+            emulator.set_ram_image(&[0; RAM_SIZE - 1]),
+            Err(EmulatorError::InvalidRamImage { len: 4095, expected: RAM_SIZE })
+        ));
+        assert_eq!(emulator.memory_dump(START_ADDR, 2), vec![0x60, 0x42]);
+    }
+
+    #[test]
+    fn memory_dump_is_cut_short_at_the_end_of_ram() {
+        let emulator = Emulator::new();
+
+        assert_eq!(emulator.memory_dump(0xFFE, 10).len(), 2);
+        assert!(emulator.memory_dump(0x1000, 10).is_empty());
+        assert!(emulator.memory_dump(0xFFFF, usize::MAX).is_empty());
+        assert_eq!(emulator.memory_dump(0, 5), FONTSET[..5].to_vec());
+    }
+
+    #[test]
+    fn register_store_uses_a_directly_set_i() {
+        // v0 = 0x42, store v0
+        let mut emulator = emulator_with(&[0x6042, 0xF055]);
+        emulator.set_i_register(0x1300);
+        assert_eq!(emulator.i_register(), 0x300);
+
+        run(&mut emulator, 2);
+        assert_eq!(emulator.memory_dump(0x300, 1), vec![0x42]);
+    }
+
+    #[test]
+    fn delay_timer_set_from_outside_is_read_by_fx07() {
+        let mut emulator = emulator_with(&[0xF307]);
+        emulator.set_delay_timer(0x2A);
+        assert_eq!(emulator.delay_timer(), 0x2A);
+
+        emulator.tick().unwrap();
+        assert_eq!(emulator.v_registers[3], 0x2A);
+    }
+
+    #[test]
+    fn registers_round_trip_through_a_snapshot() {
+        let mut emulator = emulator_with(&[0x6000, 0x6F00]);
+        fill_registers(&mut emulator);
+        let saved = emulator.registers_snapshot();
+
+        // v0 = 0, vF = 0
+        run(&mut emulator, 2);
+        assert_eq!(changed_registers(saved, emulator.registers_snapshot()), vec![0, 0xF]);
+
+        emulator.restore_registers(saved);
+        assert_eq!(emulator.registers_snapshot(), saved);
+        assert_eq!(emulator.v_register(0xF), Some(0x1F));
+    }
+
+    #[test]
+    fn font_sprite_reads_glyphs_from_ram() {
+        let emulator = Emulator::new();
+
+        assert_eq!(emulator.font_sprite(0x0), Some([0xF0, 0x90, 0x90, 0x90, 0xF0]));
+        assert_eq!(emulator.font_sprite(0xF), Some([0xF0, 0x80, 0xF0, 0x80, 0x80]));
+        assert_eq!(emulator.font_sprite(0x10), None);
+    }
+
+    #[test]
+    fn rgba_frame_buffer_uses_fg_for_lit_and_bg_for_unlit() {
+        // the top row of the "0" glyph at 0,0
+        let mut emulator = emulator_with(&[0xA000, 0xD001]);
+        run(&mut emulator, 2);
+
+        let fg = [0xFF, 0xB0, 0x00, 0xFF];
+        let bg = [0x10, 0x10, 0x10, 0xFF];
+        let buffer = emulator.frame_buffer_rgba(fg, bg);
+        assert_eq!(buffer.len(), SCREEN_WIDTH * SCREEN_HEIGHT * 4);
+
+        // pixel 3 is the last lit one, 4 the first unlit, and the second row starts unlit
+        assert_eq!(buffer[3 * 4..4 * 4], fg);
+        assert_eq!(buffer[4 * 4..5 * 4], bg);
+        assert_eq!(buffer[SCREEN_WIDTH * 4..SCREEN_WIDTH * 4 + 4], bg);
+    }
+
+    #[test]
+    fn palette_frame_buffer_writes_whole_entries() {
+        // F0 in the first plane and 3C in plane2, like plane_values_pick_palette_entries
+        let mut emulator = emulator_with(&[0xF301, 0xA208, 0xD011, 0x1206, 0xF03C]);
+        run(&mut emulator, 3);
+
+        let palette = [[0x00, 0x00, 0x00, 0xFF], [0xFF, 0x00, 0x00, 0xFF], [0x00, 0xFF, 0x00, 0xFF], [0xFF, 0xFF, 0x00, 0xFF]];
+        let buffer = emulator.frame_buffer_rgba_palette(palette);
+        assert_eq!(buffer.len(), SCREEN_WIDTH * SCREEN_HEIGHT * 4);
+
+        // one column for each plane value
+        for (col, value) in [(6, 0), (0, 1), (4, 2), (2, 3)] {
+            assert_eq!(buffer[col * 4..col * 4 + 4], palette[value], "column {}", col);
+        }
+    }
+
+    #[test]
+    fn ascii_art_round_trips() {
+        // the "0" glyph at 0,0
+        let mut emulator = emulator_with(&[0xA000, 0xD005]);
+        run(&mut emulator, 2);
+        let art = emulator.display_to_ascii();
+
+        let mut copy = Emulator::new();
+        copy.set_screen_from_ascii(&art).unwrap();
+        assert_eq!(copy.get_display(), emulator.get_display());
+        assert_eq!(copy.display_to_ascii(), art);
+    }
+
+    #[test]
+    fn bad_ascii_art_is_refused() {
+        let blank = ".".repeat(SCREEN_WIDTH);
+        let rows = |count: usize| vec![blank.as_str(); count];
+
+        let mut short_row = rows(SCREEN_HEIGHT);
+        let cut = &blank[1..];
+        short_row[5] = cut;
+        let mut bad_character = rows(SCREEN_HEIGHT);
+        let with_x = format!("x{}", &blank[1..]);
+        bad_character[7] = &with_x;
+
+        let mut emulator = Emulator::new();
+        emulator.set_pixel(0, 0, true);
+        for (lines, row) in [(rows(SCREEN_HEIGHT - 1), 31), (rows(SCREEN_HEIGHT + 1), 32), (short_row, 5), (bad_character, 7)] {
+            let result = emulator.set_screen_from_ascii(&lines.join("\n"));
+            assert!(matches!(result, Err(EmulatorError::InvalidAsciiArt { row: r }) if r == row), "row {}", row);
+        }
+        assert_eq!(emulator.lit_pixel_count(), 1);
+    }
+
+    #[test]
+    fn pbm_has_the_header_and_one_digit_per_pixel() {
+        let mut emulator = Emulator::new();
+        emulator.set_pixel(2, 1, true);
+        let pbm = emulator.display_to_pbm();
+
+        assert!(pbm.starts_with("P1\n64 32\n"));
+        let rows: Vec<&str> = pbm.lines().skip(2).collect();
+        assert_eq!(rows.len(), SCREEN_HEIGHT);
+        assert_eq!(rows[0], "0".repeat(SCREEN_WIDTH));
+        assert_eq!(&rows[1][..4], "0010");
+        assert_eq!(rows.concat().matches('1').count(), 1);
+    }
+
+    #[test]
+    fn lit_bounds_box_the_drawn_glyph() {
+        let mut emulator = emulator_with(&[0x600A, 0x6105, 0xA000, 0xD015]);
+        assert_eq!(emulator.lit_bounds(), None);
+
+        // the "0" glyph is 4 wide and 5 tall, drawn at 10,5
+        run(&mut emulator, 4);
+        assert_eq!(emulator.lit_bounds(), Some((10, 5, 13, 9)));
+    }
+
+    #[test]
+    fn lit_pixel_count_of_the_zero_glyph() {
+        let mut emulator = emulator_with(&[0xA000, 0xD005]);
+        assert_eq!(emulator.lit_pixel_count(), 0);
+
+        // F0 90 90 90 F0 is 4 + 2 + 2 + 2 + 4 pixels
+        run(&mut emulator, 2);
+        assert_eq!(emulator.lit_pixel_count(), 14);
+    }
+
+    #[test]
+    fn display_2d_is_indexed_by_row_then_column() {
+        let mut emulator = Emulator::new();
+        emulator.set_pixel(5, 3, true);
+        let display = emulator.display_2d();
+
+        assert_eq!(display.len(), SCREEN_HEIGHT);
+        assert!(display.iter().all(|row| row.len() == SCREEN_WIDTH));
+        assert!(display[3][5]);
+        assert!(!display[5][3]);
+    }
+
+    #[test]
+    fn blank_display_leaves_the_cpu_alone() {
+        // v0 = 0x11, I = "0" glyph, draw it, v1 = 0x22
+        let mut emulator = emulator_with(&[0x6011, 0xA000, 0xD005, 0x6122]);
+        run(&mut emulator, 4);
+        assert!(!emulator.last_tick_drew());
+        let registers = emulator.registers_snapshot();
+
+        emulator.blank_display();
+        assert_eq!(emulator.lit_pixel_count(), 0);
+        assert!(emulator.last_tick_drew());
+        assert_eq!(emulator.registers_snapshot(), registers);
+        assert_eq!((emulator.pc(), emulator.i_register()), (0x208, 0x000));
+    }
+
+    #[test]
+    fn screen_diff_lists_the_differing_pixels() {
+        let mut a = emulator_with(&[0xA000, 0xD005]);
+        let mut b = emulator_with(&[0xA000, 0xD005]);
+        run(&mut a, 2);
+        run(&mut b, 2);
+        assert!(screen_diff(&a, &b).is_empty());
+
+        b.set_pixel(40, 20, true);
+        a.set_pixel(1, 1, true);
+        assert_eq!(screen_diff(&a, &b), vec![(1, 1), (40, 20)]);
+    }
+
+    #[test]
+    fn input_recordings_replay_the_same_keys() {
+        let mut recorder = emulator_with(&[0x1200]);
+        recorder.start_input_recording();
+        recorder.keypress(0x1, true).unwrap();
+        recorder.keypress(0xF, true).unwrap();
+        assert_eq!(recorder.frame_input_snapshot(), 0x8002);
+        recorder.update_timers();
+        recorder.keypress(0x1, false).unwrap();
+        recorder.update_timers();
+
+        let recording = recorder.take_input_recording();
+        assert_eq!(recording, vec![0x8002, 0x8000]);
+
+        let mut player = emulator_with(&[0x1200]);
+        player.play_input_recording(recording);
+        assert_eq!(player.frame_input_snapshot(), 0x8002);
+        player.update_timers();
+        assert_eq!(player.frame_input_snapshot(), 0x8000);
+        assert!(!player.is_playing_input());
+    }
+
+    #[test]
+    fn estimate_ips_leaves_the_emulator_as_it_was() {
+        let mut emulator = emulator_with(&[0x6042, 0x1202]);
+        run(&mut emulator, 3);
+        let (ram, registers, pc, cycles) = (emulator.ram_image(), emulator.registers_snapshot(), emulator.pc(), emulator.cycle_count());
+
+        assert!(emulator.estimate_ips() > 0);
+        assert_eq!(emulator.ram_image(), ram);
+        assert_eq!(emulator.registers_snapshot(), registers);
+        assert_eq!((emulator.pc(), emulator.cycle_count()), (pc, cycles));
+    }
+
+    #[test]
+    fn quirks_reflect_a_preset_and_later_toggles() {
+        // roughly the COSMAC VIP
+        let vip = Quirks { shift_vy: true, increment_i: true, vf_reset: true, vblank: true, wrap_x: false, wrap_y: false, ..Quirks::default() };
+        let mut emulator = Emulator::new();
+        emulator.set_quirks(vip);
+        assert_eq!(*emulator.quirks(), vip);
+
+        emulator.set_quirk("jump_vx", true).unwrap();
+        assert_ne!(*emulator.quirks(), vip);
+        assert_eq!(*emulator.quirks(), Quirks { jump_vx: true, ..vip });
     }
 }
@@ -1,94 +1,506 @@
+mod recorder;
+mod settings;
+
 use chip8_core::*;
+use recorder::Recorder;
+use settings::Settings;
 
-use std::fs::File;
-use std::io::Read;
 use std::env;
+use std::process;
+use std::thread;
+use std::time::{Duration, Instant};
 
-use sdl2::event::Event;
+use sdl2::audio::{AudioCallback, AudioSpecDesired};
+use sdl2::event::{Event, WindowEvent};
 use sdl2::keyboard::Keycode;
-use sdl2::pixels::Color;
+use sdl2::mouse::MouseButton;
+use sdl2::pixels::{Color, PixelFormatEnum};
 use sdl2::rect::Rect;
-use sdl2::render::Canvas;
+use sdl2::render::{BlendMode, Canvas, Texture};
 use sdl2::video::Window;
 
 const SCALE: u32 = 20;
 const WINDOW_WIDTH: u32 = (SCREEN_WIDTH as u32) * SCALE;
 const WINDOW_HEIGHT: u32 = (SCREEN_HEIGHT as u32) * SCALE;
 const TICKS_PER_FRAME: usize = 2;
+const BEEP_FREQUENCY: f32 = 440.0; // hz
+const BEEP_VOLUME: f32 = 0.25;
+const AUDIO_SAMPLE_RATE: i32 = 44100;
+const BORDER_WIDTH: u32 = 4;
+const ERROR_BORDER_COLOR: Color = Color::RGB(255, 0, 0);
+const HALTED_BORDER_COLOR: Color = Color::RGB(96, 72, 0); // dim amber, the rom finished rather than failed
+const SCANLINE_ALPHA: u8 = 96; // how dark the --scanlines gaps between pixel rows are
+const KEYPAD_CELL: u32 = 40; // size of one key in the F4 keypad overlay
+const KEYPAD_GLYPH_SCALE: u32 = 4; // window pixels per font pixel for the key labels
+const FRAME_DURATION: Duration = Duration::from_micros(16_667); // 60fps, used when there's nothing to present
+
+// pixel colours, set with --fg/--bg and remembered in the settings
+#[derive(Clone, Copy)]
+struct Palette {
+    fg: Color, // lit pixels
+    bg: Color, // unlit pixels
+}
+
+const DEFAULT_PALETTE: Palette = Palette {
+    fg: Color::RGB(255, 255, 255),
+    bg: Color::RGB(0, 0, 0),
+};
+
+// keys the event loop handles before the keypad, so they can't be bound to a keypad key
+const HOTKEYS: [Keycode; 9] = [
+    Keycode::Escape,
+    Keycode::P,
+    Keycode::M,
+    Keycode::F2,
+    Keycode::F3,
+    Keycode::F4,
+    Keycode::F5,
+    Keycode::F6,
+    Keycode::F9,
+];
+
+// the 1234/QWER/ASDF/ZXCV block of the keyboard, laid out like the keypad
+const KEYBOARD_BLOCK: [[Keycode; 4]; 4] = [
+    [Keycode::Num1, Keycode::Num2, Keycode::Num3, Keycode::Num4],
+    [Keycode::Q, Keycode::W, Keycode::E, Keycode::R],
+    [Keycode::A, Keycode::S, Keycode::D, Keycode::F],
+    [Keycode::Z, Keycode::X, Keycode::C, Keycode::V],
+];
+
+// generates the beep tone, keeping the phase between callbacks so toggling doesnt click
+struct SquareWave {
+    phase_inc: f32,
+    phase: f32,
+    volume: f32,
+}
+
+impl AudioCallback for SquareWave {
+    type Channel = f32;
+
+    fn callback(&mut self, out: &mut [f32]) {
+        for sample in out.iter_mut() {
+            *sample = if self.phase <= 0.5 { self.volume } else { -self.volume };
+            self.phase = (self.phase + self.phase_inc) % 1.0;
+        }
+    }
+}
 
 fn main() {
-    let args: Vec<_> = env::args().collect();
-    let mut game_speed: usize = TICKS_PER_FRAME;
-    let game_file = &args[1];
+    // saved settings first, flags on the command line override them and are remembered for next time
+    let mut settings = Settings::load();
+    let mut game_speed = settings.game_speed;
+    let mut beep_frequency = settings.beep_frequency;
+    let mut integer_scaling = settings.integer_scaling;
+    let mut scanlines = settings.scanlines;
+    let mut auto_pause = settings.auto_pause;
+    let mut playlist_interval = None;
+    let mut positional_args = Vec::new();
+
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--frequency" => {
+                beep_frequency = args.next().and_then(|value| value.parse().ok()).unwrap_or(beep_frequency);
+            },
+            "--integer-scaling" => integer_scaling = true,
+            "--no-integer-scaling" => integer_scaling = false,
+            "--scanlines" => scanlines = true,
+            "--no-scanlines" => scanlines = false,
+            "--auto-pause" => auto_pause = true,
+            "--no-auto-pause" => auto_pause = false,
+            "--fg" | "--bg" => {
+                match args.next().as_deref().and_then(settings::parse_color) {
+                    Some(color) if arg == "--fg" => settings.palette.fg = color,
+                    Some(color) => settings.palette.bg = color,
+                    None => eprintln!("Ignoring {}, colours are written as rrggbb", arg),
+                }
+            },
+            "--quirk" => {
+                let value = args.next().unwrap_or_default();
+                let applied = match value.split_once('=') {
+                    Some((name, value)) => settings.set_quirk(name.trim(), value.trim()),
+                    None => false,
+                };
+                if !applied {
+                    eprintln!("Ignoring --quirk {}, expected <name>=<true|false>", value);
+                }
+            },
+            "--key" => {
+                let value = args.next().unwrap_or_default();
+                let bound = match value.split_once('=') {
+                    Some((hex, key_name)) => settings.bind_key(hex.trim(), key_name.trim()),
+                    None => Err("expected <hex key>=<keyboard key name>".to_string()),
+                };
+                if let Err(reason) = bound {
+                    eprintln!("Ignoring --key {}, {}", value, reason);
+                }
+            },
+            "--playlist-interval" => {
+                playlist_interval = args
+                    .next()
+                    .and_then(|value| value.parse::<f64>().ok())
+                    .filter(|seconds| seconds.is_finite() && *seconds > 0.0)
+                    .map(Duration::from_secs_f64);
+            },
+            _ => positional_args.push(arg),
+        }
+    }
 
-    if args.len() > 2 {
-        game_speed = args[2].parse().unwrap_or(TICKS_PER_FRAME);
+    // one or more rom paths, then optionally the speed as the last argument
+    if positional_args.len() > 1 {
+        if let Some(speed) = positional_args.last().and_then(|arg| arg.parse().ok()) {
+            game_speed = speed;
+            positional_args.pop();
+        }
     }
 
 
     // Setup SDL
     let sdl_context = sdl2::init().unwrap();
     let video_subsystem = sdl_context.video().unwrap();
-    let window = video_subsystem
-        .window("Chip-8 Emulator", WINDOW_WIDTH, WINDOW_HEIGHT)
-        .position_centered()
-        .opengl()
-        .build()
-        .unwrap();
+    let mut window_builder = video_subsystem.window("Chip-8 Emulator", WINDOW_WIDTH, WINDOW_HEIGHT);
+    window_builder.position_centered().opengl();
+    if integer_scaling {
+        window_builder.resizable();
+    }
+    let window = window_builder.build().unwrap();
 
     let mut canvas = window.into_canvas().present_vsync().build().unwrap();
     canvas.clear();
     canvas.present();
 
+    // the display at native resolution, only used with --integer-scaling
+    sdl2::hint::set("SDL_RENDER_SCALE_QUALITY", "nearest");
+    let texture_creator = canvas.texture_creator();
+    let mut texture = texture_creator
+        .create_texture_streaming(PixelFormatEnum::RGBA32, SCREEN_WIDTH as u32, SCREEN_HEIGHT as u32)
+        .unwrap();
+
     let mut event_pump = sdl_context.event_pump().unwrap();
 
+    // opened once, then paused and resumed following the sound timer
+    let audio_subsystem = sdl_context.audio().unwrap();
+    let desired_spec = AudioSpecDesired {
+        freq: Some(AUDIO_SAMPLE_RATE),
+        channels: Some(1),
+        samples: None,
+    };
+    let audio_device = audio_subsystem
+        .open_playback(None, &desired_spec, |spec| SquareWave {
+            phase_inc: beep_frequency / spec.freq as f32,
+            phase: 0.0,
+            volume: BEEP_VOLUME,
+        })
+        .unwrap();
+
+    let palette = settings.palette;
+    let keymap = settings.keymap;
+
     let mut chip8 = Emulator::new();
+    chip8.set_quirks(settings.quirks);
+    chip8.set_sound_enabled(!settings.muted);
 
-    let mut rom = File::open(game_file).expect("Unable to open file");
-    let mut buffer = Vec::new();
+    // every rom is kept in its own slot so the playlist can switch without touching the disk,
+    // ones that can't be read are left out
+    let mut playlist = Vec::new();
+    for path in positional_args {
+        match chip8.load_slot_path(playlist.len(), &path) {
+            Ok(_) => playlist.push(path),
+            Err(err) => eprintln!("Skipping rom {}: {}", path, err),
+        }
+    }
+    if playlist.is_empty() {
+        eprintln!("No rom to play, usage: desktop [flags] <rom> [more roms] [speed]");
+        process::exit(1);
+    }
+    chip8.activate_slot(0).expect("the first playlist entry was just stored");
 
-    rom.read_to_end(&mut buffer).unwrap();
-    chip8.load(&buffer);
+    // with --playlist-interval and more than one rom, the next rom starts once the interval is up or the rom halts
+    let attract_mode = playlist_interval.is_some() && playlist.len() > 1;
+    if playlist_interval.is_some() && !attract_mode {
+        eprintln!("Only one rom, --playlist-interval has nothing to advance to");
+    }
+    let mut current_rom = 0;
+    let mut rom_started = Instant::now();
+
+    // P pauses the rom, F2 toggles paint mode where clicking flips pixels while paused, F3 pauses and steps back a frame,
+    // F4 shows the keypad with the held keys lit up, F6 skips to the next rom in the playlist, M mutes the beep,
+    // F9 starts and stops recording a gif
+    let mut paused = false;
+    let mut paint_mode = false;
+    let mut show_keypad = settings.show_keypad;
+    // set when the rom hits an error, the emulator pauses with a red border until F5 reloads it
+    let mut crashed = false;
+    // set when the rom ends on a jump to itself, ticking stops with an amber border until F5 (unless --no-auto-pause)
+    let mut halted = false;
+    let mut recorder: Option<Recorder> = None;
+    // only present when something on screen changed, start with one so the window isn't blank
+    let mut redraw = true;
 
     'gameloop: loop {
+        let mut next_rom = false;
+
         for evt in event_pump.poll_iter() {
             match evt {
+                // SDL repeats KeyDown while a key is held, only the first press counts
+                // (this also stops P, F2 and F5 from flickering on and off)
+                Event::KeyDown{repeat: true, ..} => (),
                 Event::Quit{..} |
                 Event::KeyDown{
-                    keycode: Some(Keycode::Escape), ..}=> {
+                    keycode: Some(Keycode::Escape), repeat: false, ..}=> {
                     break 'gameloop;
                 },
-                Event::KeyDown{keycode: Some(key), ..} => {
-                    if let Some(k) = key2btn(key) {
-                        chip8.keypress(k, true);
+                Event::KeyDown{keycode: Some(Keycode::P), ..} => {
+                    paused = !paused;
+                },
+                Event::KeyDown{keycode: Some(Keycode::M), ..} => {
+                    chip8.set_sound_enabled(!chip8.sound_enabled());
+                },
+                Event::KeyDown{keycode: Some(Keycode::F2), ..} => {
+                    paint_mode = !paint_mode;
+                },
+                Event::KeyDown{keycode: Some(Keycode::F3), ..} => {
+                    // frames are only kept once rewind is first asked for, so this press may have nothing to go back to
+                    chip8.set_frame_rewind(true);
+                    paused = true;
+                    if chip8.step_back_frame() {
+                        crashed = false;
+                        halted = false;
+                        redraw = true;
+                    }
+                },
+                Event::KeyDown{keycode: Some(Keycode::F4), ..} => {
+                    show_keypad = !show_keypad;
+                    redraw = true;
+                },
+                Event::KeyDown{keycode: Some(Keycode::F6), ..} => {
+                    next_rom = true;
+                },
+                Event::KeyDown{keycode: Some(Keycode::F9), ..} => {
+                    match recorder.take() {
+                        Some(recording) => finish_recording(recording),
+                        None => match Recorder::start(palette) {
+                            Ok(recording) => {
+                                println!("Recording to {}, press F9 again to stop", recording.path().display());
+                                recorder = Some(recording);
+                            },
+                            Err(err) => eprintln!("Unable to start recording: {}", err),
+                        },
+                    }
+                },
+                Event::KeyDown{keycode: Some(Keycode::F5), ..} => {
+                    // read from disk again so a freshly rebuilt rom gets picked up
+                    if let Err(err) = chip8.load_slot_path(current_rom, &playlist[current_rom]) {
+                        eprintln!("Unable to reload rom, restarting the previous copy: {}", err);
+                    }
+                    chip8.activate_slot(current_rom).expect("every playlist entry has a stored slot");
+                    paused = false;
+                    crashed = false;
+                    halted = false;
+                    redraw = true;
+                },
+                // only paint while paused so we dont fight the rom's own drawing
+                Event::MouseButtonDown{mouse_btn: MouseButton::Left, x, y, ..} if paused && paint_mode => {
+                    let area = display_area(&canvas, integer_scaling);
+                    if area.contains_point((x, y)) {
+                        let pixel_x = (x - area.x()) as usize * SCREEN_WIDTH / area.width() as usize;
+                        let pixel_y = (y - area.y()) as usize * SCREEN_HEIGHT / area.height() as usize;
+                        let pixel_is_on = chip8.get_pixel(pixel_x, pixel_y);
+                        chip8.set_pixel(pixel_x, pixel_y, !pixel_is_on);
+                        redraw = true;
+                    }
+                },
+                Event::KeyDown{keycode: Some(key), repeat: false, ..} => {
+                    if let Some(k) = key2btn(key, &keymap) {
+                        chip8.keypress(k, true).expect("key2btn only maps to keys 0x0 to 0xF");
+                        redraw |= show_keypad;
                     }
                 },
                 Event::KeyUp{keycode: Some(key), ..} => {
-                    if let Some(k) = key2btn(key) {
-                        chip8.keypress(k, false);
+                    if let Some(k) = key2btn(key, &keymap) {
+                        chip8.keypress(k, false).expect("key2btn only maps to keys 0x0 to 0xF");
+                        redraw |= show_keypad;
                     }
                 },
+                // key up events go to whatever window has focus now, so let go of everything
+                Event::Window{win_event: WindowEvent::FocusLost, ..} => {
+                    chip8.reset_keys();
+                    redraw = true;
+                },
+                // exposed or resized, the old frame is gone
+                Event::Window{..} => {
+                    redraw = true;
+                },
                 _ => ()
             }
         }
 
-        for _ in 0..(game_speed | TICKS_PER_FRAME) {
-            chip8.tick();
+        // only frames the rom actually runs are recorded, a pause doesn't show up in the gif
+        let running = !paused && !halted;
+        if running {
+            for _ in 0..(game_speed | TICKS_PER_FRAME) {
+                let result = chip8.tick();
+                redraw |= chip8.last_tick_drew();
+
+                if let Err(err) = result {
+                    eprintln!("Emulator error: {}", err);
+                    eprintln!("Emulator paused, press F5 to reload the rom or Esc to quit");
+
+                    paused = true;
+                    crashed = true;
+                    redraw = true;
+                    break;
+                }
+            }
+            chip8.update_timers();
+
+            // nothing can change any more, so stop ticking instead of spinning on the jump
+            if auto_pause && chip8.is_halted() && !attract_mode {
+                println!("Rom halted, press F5 to run it again or Esc to quit");
+                halted = true;
+                redraw = true;
+            }
+        }
+
+        if let Some(recording) = recorder.as_mut().filter(|_| running) {
+            if let Err(err) = recording.add_frame(&chip8) {
+                eprintln!("Recording stopped, unable to write frame: {}", err);
+                recorder = None;
+            }
+        }
+
+        // a crashed rom moves on too so an unattended display doesn't get stuck, only a manual pause holds it
+        if let Some(interval) = playlist_interval.filter(|_| attract_mode) {
+            let interval_up = rom_started.elapsed() >= interval;
+            next_rom |= (!paused || crashed) && (interval_up || chip8.is_halted());
         }
-        chip8.update_timers();
-        draw_screen(&chip8, &mut canvas);
+
+        if next_rom && playlist.len() > 1 {
+            current_rom = (current_rom + 1) % playlist.len();
+            chip8.activate_slot(current_rom).expect("every playlist entry has a stored slot");
+            println!("Playing {}", playlist[current_rom]);
+
+            rom_started = Instant::now();
+            paused = false;
+            crashed = false;
+            halted = false;
+            redraw = true;
+        }
+
+        if chip8.is_beeping() && !paused && !halted {
+            audio_device.resume();
+        } else {
+            audio_device.pause();
+        }
+
+        if !redraw {
+            // present() normally paces the loop through vsync, so wait out the frame ourselves
+            thread::sleep(FRAME_DURATION);
+            continue;
+        }
+
+        let border = if crashed {
+            Some(ERROR_BORDER_COLOR)
+        } else if halted {
+            Some(HALTED_BORDER_COLOR)
+        } else {
+            None
+        };
+        if integer_scaling {
+            draw_screen_scaled(&chip8, &mut canvas, &mut texture, palette, scanlines, show_keypad, border);
+        } else {
+            draw_screen(&chip8, &mut canvas, palette, scanlines, show_keypad, border);
+        }
+        redraw = false;
+    }
+
+    // quitting mid-recording still keeps what was captured
+    if let Some(recording) = recorder {
+        finish_recording(recording);
     }
+
+    Settings {
+        beep_frequency,
+        game_speed,
+        integer_scaling,
+        scanlines,
+        auto_pause,
+        muted: !chip8.sound_enabled(),
+        show_keypad,
+        ..settings
+    }
+    .save();
 }
 
-fn draw_screen(emulator: &Emulator, canvas: &mut Canvas<Window>) {
-    // clear canvas
+// where the chip8 display ends up inside the window
+fn display_area(canvas: &Canvas<Window>, integer_scaling: bool) -> Rect {
+    if !integer_scaling {
+        return Rect::new(0, 0, WINDOW_WIDTH, WINDOW_HEIGHT);
+    }
+
+    // largest whole number scale that fits, centered with black bars around it
+    let (output_width, output_height) = canvas.output_size().unwrap();
+    let scale = (output_width / SCREEN_WIDTH as u32).min(output_height / SCREEN_HEIGHT as u32).max(1);
+    let width = SCREEN_WIDTH as u32 * scale;
+    let height = SCREEN_HEIGHT as u32 * scale;
+
+    Rect::new(
+        (output_width.saturating_sub(width) / 2) as i32,
+        (output_height.saturating_sub(height) / 2) as i32,
+        width,
+        height,
+    )
+}
+
+// uploads the display as one texture and lets SDL scale it with nearest neighbour
+fn draw_screen_scaled(
+    emulator: &Emulator,
+    canvas: &mut Canvas<Window>,
+    texture: &mut Texture,
+    palette: Palette,
+    scanlines: bool,
+    keypad: bool,
+    border: Option<Color>,
+) {
+    let frame = emulator.frame_buffer_rgba(rgba(palette.fg), rgba(palette.bg));
+    texture.update(None, &frame, SCREEN_WIDTH * 4).unwrap();
+
     canvas.set_draw_color(Color::RGB(0, 0, 0));
     canvas.clear();
+    let area = display_area(canvas, true);
+    canvas.copy(texture, None, area).unwrap();
+
+    if scanlines {
+        draw_scanlines(canvas, area);
+    }
+
+    if keypad {
+        draw_keypad(emulator, canvas, area);
+    }
+
+    if let Some(color) = border {
+        draw_border(canvas, color);
+    }
+
+    canvas.present();
+}
+
+fn draw_screen(
+    emulator: &Emulator,
+    canvas: &mut Canvas<Window>,
+    palette: Palette,
+    scanlines: bool,
+    keypad: bool,
+    border: Option<Color>,
+) {
+    // clear canvas
+    canvas.set_draw_color(palette.bg);
+    canvas.clear();
 
     let screen = emulator.get_display();
 
-    // set draw color to white
-    canvas.set_draw_color(Color::RGB(255, 255, 255));
+    // set draw color to the lit pixel colour
+    canvas.set_draw_color(palette.fg);
 
     // go through screen pixels and add rect for each active pixel
     for (i, pixel_is_on) in screen.iter().enumerate() {
@@ -102,27 +514,123 @@ fn draw_screen(emulator: &Emulator, canvas: &mut Canvas<Window>) {
             canvas.fill_rect(pixel).unwrap();
         }
     }
+
+    if scanlines {
+        draw_scanlines(canvas, display_area(canvas, false));
+    }
+
+    if keypad {
+        draw_keypad(emulator, canvas, display_area(canvas, false));
+    }
+
+    if let Some(color) = border {
+        draw_border(canvas, color);
+    }
+
     canvas.present();
 }
 
-fn key2btn(key: Keycode) -> Option<usize> {
-    match key {
-        Keycode::Num1 =>    Some(0x1),
-        Keycode::Num2 =>    Some(0x2),
-        Keycode::Num3 =>    Some(0x3),
-        Keycode::Num4 =>    Some(0xC),
-        Keycode::Q =>       Some(0x4),
-        Keycode::W =>       Some(0x5),
-        Keycode::E =>       Some(0x6),
-        Keycode::R =>       Some(0xD),
-        Keycode::A =>       Some(0x7),
-        Keycode::S =>       Some(0x8),
-        Keycode::D =>       Some(0x9),
-        Keycode::F =>       Some(0xE),
-        Keycode::Z =>       Some(0xA),
-        Keycode::X =>       Some(0x0),
-        Keycode::C =>       Some(0xB),
-        Keycode::V =>       Some(0xF),
-        _ =>                None,
+// darkens the bottom quarter of every pixel row inside area to look like an old CRT
+fn draw_scanlines(canvas: &mut Canvas<Window>, area: Rect) {
+    let row_height = area.height() / SCREEN_HEIGHT as u32;
+    let line_height = (row_height / 4).max(1);
+
+    let lines: Vec<Rect> = (0..SCREEN_HEIGHT as u32)
+        .map(|row| {
+            let y = area.y() + (row * row_height + row_height - line_height) as i32;
+            Rect::new(area.x(), y, area.width(), line_height)
+        })
+        .collect();
+
+    canvas.set_blend_mode(BlendMode::Blend);
+    canvas.set_draw_color(Color::RGBA(0, 0, 0, SCANLINE_ALPHA));
+    canvas.fill_rects(&lines).unwrap();
+    canvas.set_blend_mode(BlendMode::None);
+}
+
+// the hex keypad in the bottom right corner of area, held keys are lit and labelled with the chip8 font
+fn draw_keypad(emulator: &Emulator, canvas: &mut Canvas<Window>, area: Rect) {
+    let keys = emulator.keys_mask();
+    let left = area.right() - (KEYPAD_CELL * 4) as i32;
+    let top = area.bottom() - (KEYPAD_CELL * 4) as i32;
+    let glyph_left = ((KEYPAD_CELL - 4 * KEYPAD_GLYPH_SCALE) / 2) as i32;
+    let glyph_top = ((KEYPAD_CELL - 5 * KEYPAD_GLYPH_SCALE) / 2) as i32;
+
+    canvas.set_blend_mode(BlendMode::Blend);
+    for (row, keys_in_row) in keypad_layout().iter().enumerate() {
+        for (column, &key) in keys_in_row.iter().enumerate() {
+            let x = left + (column as u32 * KEYPAD_CELL) as i32;
+            let y = top + (row as u32 * KEYPAD_CELL) as i32;
+            let held = keys & (1 << key) != 0;
+
+            let (cell_color, label_color) = if held {
+                (Color::RGBA(255, 170, 0, 220), Color::RGB(0, 0, 0))
+            } else {
+                (Color::RGBA(64, 64, 64, 200), Color::RGB(255, 255, 255))
+            };
+            canvas.set_draw_color(cell_color);
+            canvas.fill_rect(Rect::new(x + 1, y + 1, KEYPAD_CELL - 2, KEYPAD_CELL - 2)).unwrap();
+
+            // glyphs are 4 pixels wide, in the top nibble of each of the 5 rows
+            let glyph = emulator.font_sprite(key).unwrap_or_default();
+            let label: Vec<Rect> = (0..5)
+                .flat_map(|glyph_row| (0..4).map(move |glyph_column| (glyph_row, glyph_column)))
+                .filter(|&(glyph_row, glyph_column)| glyph[glyph_row] & (0x80 >> glyph_column) != 0)
+                .map(|(glyph_row, glyph_column)| {
+                    Rect::new(
+                        x + glyph_left + (glyph_column as u32 * KEYPAD_GLYPH_SCALE) as i32,
+                        y + glyph_top + (glyph_row as u32 * KEYPAD_GLYPH_SCALE) as i32,
+                        KEYPAD_GLYPH_SCALE,
+                        KEYPAD_GLYPH_SCALE,
+                    )
+                })
+                .collect();
+            canvas.set_draw_color(label_color);
+            canvas.fill_rects(&label).unwrap();
+        }
     }
+    canvas.set_blend_mode(BlendMode::None);
+}
+
+// border around the window, red while stopped on an error and amber once the rom halted
+fn draw_border(canvas: &mut Canvas<Window>, color: Color) {
+    let (width, height) = canvas.output_size().unwrap();
+
+    canvas.set_draw_color(color);
+    let border = [
+        Rect::new(0, 0, width, BORDER_WIDTH),
+        Rect::new(0, height.saturating_sub(BORDER_WIDTH) as i32, width, BORDER_WIDTH),
+        Rect::new(0, 0, BORDER_WIDTH, height),
+        Rect::new(width.saturating_sub(BORDER_WIDTH) as i32, 0, BORDER_WIDTH, height),
+    ];
+    canvas.fill_rects(&border).unwrap();
+}
+
+// reports where the gif went, or why it couldn't be finished
+fn finish_recording(recorder: Recorder) {
+    match recorder.finish() {
+        Ok(path) => println!("Saved recording to {}", path.display()),
+        Err(err) => eprintln!("Unable to finish recording: {}", err),
+    }
+}
+
+// the 1234/QWER/ASDF/ZXCV block of the keyboard stands in for the keypad, position by position,
+// until --key remaps it
+fn default_keymap() -> [Keycode; 16] {
+    let mut keymap = [Keycode::Num1; 16];
+    for (row, keys) in keypad_layout().iter().enumerate() {
+        for (column, &key) in keys.iter().enumerate() {
+            keymap[key as usize] = KEYBOARD_BLOCK[row][column];
+        }
+    }
+
+    keymap
+}
+
+fn key2btn(key: Keycode, keymap: &[Keycode; 16]) -> Option<usize> {
+    keymap.iter().position(|&bound| bound == key)
+}
+
+fn rgba(color: Color) -> [u8; 4] {
+    [color.r, color.g, color.b, 255]
 }
@@ -0,0 +1,125 @@
+use crate::{rgba, Palette};
+
+use chip8_core::{Emulator, SCREEN_HEIGHT, SCREEN_WIDTH};
+use gif::{Encoder, EncodingError, Frame, Repeat};
+
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const GIF_SCALE: usize = 8; // output pixels per chip-8 pixel, 64x32 is too small to watch
+const FRAME_RATE: u64 = 60; // frames the desktop app runs per second
+const MIN_DELAY: u16 = 2; // hundredths of a second, most viewers slow anything shorter down to 10
+
+// streams the display into an animated gif, one frame per emulated frame. frames go straight to the file
+// so a long recording doesn't grow in memory, and unchanged frames just extend the previous frame's delay.
+// the display size is fixed in this core, so there is no resolution change to handle mid-recording
+pub struct Recorder {
+    encoder: Encoder<BufWriter<File>>,
+    path: PathBuf,
+    fg: [u8; 4], // lit pixel colour in the frame_buffer_rgba output
+    bg: [u8; 4],
+    pending: Vec<u8>, // indexed pixels of the frame not written yet, empty before the first frame
+    pending_start: u64, // frame count when the pending frame first showed
+    frames: u64, // emulated frames seen so far
+}
+
+impl Recorder {
+    // starts a chip8_<unix time>.gif in the working directory, drawn in the palette's colours
+    pub fn start(palette: Palette) -> Result<Recorder, EncodingError> {
+        let seconds = SystemTime::now().duration_since(UNIX_EPOCH).map(|time| time.as_secs()).unwrap_or(0);
+        let path = PathBuf::from(format!("chip8_{}.gif", seconds));
+
+        let (fg, bg) = (rgba(palette.fg), rgba(palette.bg));
+        let colors: Vec<u8> = [bg, fg].iter().flat_map(|color| color[..3].to_vec()).collect();
+        let file = BufWriter::new(File::create(&path)?);
+        let width = (SCREEN_WIDTH * GIF_SCALE) as u16;
+        let height = (SCREEN_HEIGHT * GIF_SCALE) as u16;
+        let mut encoder = Encoder::new(file, width, height, &colors)?;
+        encoder.set_repeat(Repeat::Infinite)?;
+
+        Ok(Recorder {
+            encoder,
+            path,
+            fg,
+            bg,
+            pending: Vec::new(),
+            pending_start: 0,
+            frames: 0,
+        })
+    }
+
+    pub fn path(&self) -> &PathBuf {
+        &self.path
+    }
+
+    // call once per emulated frame. a frame that would show for less than MIN_DELAY is dropped and
+    // the next one takes over its time, so the gif keeps the emulated pace
+    pub fn add_frame(&mut self, emulator: &Emulator) -> Result<(), EncodingError> {
+        let pixels = scale(&emulator.frame_buffer_rgba(self.fg, self.bg), self.fg);
+
+        if self.pending.is_empty() {
+            self.pending = pixels;
+        } else if pixels != self.pending {
+            let delay = self.pending_delay();
+            if delay >= MIN_DELAY {
+                self.write_pending(delay)?;
+                self.pending_start = self.frames;
+            }
+            self.pending = pixels;
+        }
+        self.frames += 1;
+
+        Ok(())
+    }
+
+    // writes the last frame and the gif trailer, returning where it went
+    pub fn finish(mut self) -> Result<PathBuf, EncodingError> {
+        if !self.pending.is_empty() {
+            let delay = self.pending_delay();
+            self.write_pending(delay.max(MIN_DELAY))?;
+        }
+
+        self.encoder.into_inner()?.flush()?;
+
+        Ok(self.path)
+    }
+
+    // how long the pending frame has shown, in hundredths of a second (gif delays can't go finer)
+    fn pending_delay(&self) -> u16 {
+        let shown = centiseconds(self.frames) - centiseconds(self.pending_start);
+        shown.min(u16::MAX as u64) as u16
+    }
+
+    fn write_pending(&mut self, delay: u16) -> Result<(), EncodingError> {
+        let width = (SCREEN_WIDTH * GIF_SCALE) as u16;
+        let height = (SCREEN_HEIGHT * GIF_SCALE) as u16;
+        let mut frame = Frame::from_indexed_pixels(width, height, self.pending.as_slice(), None);
+        frame.delay = delay;
+
+        self.encoder.write_frame(&frame)
+    }
+}
+
+// rgba display to palette indices (0 off, 1 on), each pixel blown up to a GIF_SCALE square
+fn scale(rgba: &[u8], fg: [u8; 4]) -> Vec<u8> {
+    let mut pixels = Vec::with_capacity(SCREEN_WIDTH * SCREEN_HEIGHT * GIF_SCALE * GIF_SCALE);
+
+    for row in rgba.chunks_exact(SCREEN_WIDTH * 4) {
+        let line: Vec<u8> = row
+            .chunks_exact(4)
+            .flat_map(|pixel| std::iter::repeat_n((pixel == fg) as u8, GIF_SCALE))
+            .collect();
+        for _ in 0..GIF_SCALE {
+            pixels.extend_from_slice(&line);
+        }
+    }
+
+    pixels
+}
+
+// when a frame shows, in hundredths of a second rounded
+fn centiseconds(frame: u64) -> u64 {
+    (frame * 100 + FRAME_RATE / 2) / FRAME_RATE
+}
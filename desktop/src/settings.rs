@@ -0,0 +1,175 @@
+use crate::{default_keymap, Palette, BEEP_FREQUENCY, DEFAULT_PALETTE, HOTKEYS, TICKS_PER_FRAME};
+
+use chip8_core::Quirks;
+use sdl2::keyboard::Keycode;
+use sdl2::pixels::Color;
+
+use std::fmt::Write;
+use std::fs;
+use std::path::PathBuf;
+
+const SETTINGS_DIR: &str = "chip8_emulator";
+const SETTINGS_FILE: &str = "settings.txt";
+
+// options remembered between runs, saved as "name = value" lines in the user's config dir
+pub struct Settings {
+    pub beep_frequency: f32,
+    pub game_speed: usize,
+    pub integer_scaling: bool,
+    pub scanlines: bool,
+    pub auto_pause: bool,
+    pub muted: bool,
+    pub show_keypad: bool,
+    pub palette: Palette,
+    pub quirks: Quirks, // saved as one "quirk.<name>" line per quirk
+    pub keymap: [Keycode; 16], // keyboard key for each hex key, saved as "key.<hex>" lines
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            beep_frequency: BEEP_FREQUENCY,
+            game_speed: TICKS_PER_FRAME,
+            integer_scaling: false,
+            scanlines: false,
+            auto_pause: true,
+            muted: false,
+            show_keypad: false,
+            palette: DEFAULT_PALETTE,
+            quirks: Quirks::default(),
+            keymap: default_keymap(),
+        }
+    }
+}
+
+impl Settings {
+    // defaults when there is no settings file yet, lines that can't be read are skipped with a warning
+    pub fn load() -> Self {
+        let mut settings = Settings::default();
+
+        let contents = match settings_path().map(fs::read_to_string) {
+            Some(Ok(contents)) => contents,
+            _ => return settings,
+        };
+
+        for line in contents.lines().map(str::trim).filter(|line| !line.is_empty()) {
+            if !settings.apply(line) {
+                eprintln!("Ignoring unreadable setting '{}'", line);
+            }
+        }
+
+        settings
+    }
+
+    // failing to save only costs the settings, so it's reported and otherwise ignored
+    pub fn save(&self) {
+        let path = match settings_path() {
+            Some(path) => path,
+            None => return,
+        };
+
+        let mut contents = format!(
+            "frequency = {}\nspeed = {}\ninteger_scaling = {}\nscanlines = {}\nauto_pause = {}\nmuted = {}\nshow_keypad = {}\n",
+            self.beep_frequency,
+            self.game_speed,
+            self.integer_scaling,
+            self.scanlines,
+            self.auto_pause,
+            self.muted,
+            self.show_keypad,
+        );
+        let _ = writeln!(contents, "fg = {}\nbg = {}", format_color(self.palette.fg), format_color(self.palette.bg));
+        for (name, value) in self.quirks.named() {
+            let _ = writeln!(contents, "quirk.{} = {}", name, value);
+        }
+        for (idx, key) in self.keymap.iter().enumerate() {
+            let _ = writeln!(contents, "key.{:X} = {}", idx, key.name());
+        }
+
+        let result = match path.parent() {
+            Some(dir) => fs::create_dir_all(dir).and_then(|_| fs::write(&path, contents)),
+            None => fs::write(&path, contents),
+        };
+        if let Err(err) = result {
+            eprintln!("Unable to save settings to {}: {}", path.display(), err);
+        }
+    }
+
+    // "shift_vy", "true", false when the quirk or the value isn't known
+    pub fn set_quirk(&mut self, name: &str, value: &str) -> bool {
+        match value.parse() {
+            Ok(value) => self.quirks.set(name, value).is_ok(),
+            Err(_) => false,
+        }
+    }
+
+    // binds hex key "A" to the keyboard key named "Space" (SDL's key names), the error says why it couldn't.
+    // a keyboard key only drives one hex key, so the hex key it drove before gets this one's old key
+    pub fn bind_key(&mut self, hex: &str, key_name: &str) -> Result<(), String> {
+        let idx = match u8::from_str_radix(hex, 16) {
+            Ok(idx) if (idx as usize) < self.keymap.len() => idx as usize,
+            _ => return Err(format!("'{}' is not a hex key from 0 to F", hex)),
+        };
+        let key = match Keycode::from_name(key_name) {
+            Some(key) => key,
+            None => return Err(format!("'{}' is not an SDL key name", key_name)),
+        };
+        if HOTKEYS.contains(&key) {
+            return Err(format!("{} is a hotkey and can't be bound to a keypad key", key.name()));
+        }
+
+        if let Some(other) = self.keymap.iter().position(|&bound| bound == key) {
+            self.keymap[other] = self.keymap[idx];
+        }
+        self.keymap[idx] = key;
+
+        Ok(())
+    }
+
+    // false when the line isn't a known "name = value" pair
+    fn apply(&mut self, line: &str) -> bool {
+        let (name, value) = match line.split_once('=') {
+            Some((name, value)) => (name.trim(), value.trim()),
+            None => return false,
+        };
+
+        if let Some(quirk) = name.strip_prefix("quirk.") {
+            return self.set_quirk(quirk, value);
+        }
+        if let Some(hex) = name.strip_prefix("key.") {
+            return self.bind_key(hex, value).map_err(|reason| eprintln!("{}", reason)).is_ok();
+        }
+
+        match name {
+            "frequency" => value.parse().map(|value| self.beep_frequency = value).is_ok(),
+            "speed" => value.parse().map(|value| self.game_speed = value).is_ok(),
+            "integer_scaling" => value.parse().map(|value| self.integer_scaling = value).is_ok(),
+            "scanlines" => value.parse().map(|value| self.scanlines = value).is_ok(),
+            "auto_pause" => value.parse().map(|value| self.auto_pause = value).is_ok(),
+            "muted" => value.parse().map(|value| self.muted = value).is_ok(),
+            "show_keypad" => value.parse().map(|value| self.show_keypad = value).is_ok(),
+            "fg" => parse_color(value).map(|color| self.palette.fg = color).is_some(),
+            "bg" => parse_color(value).map(|color| self.palette.bg = color).is_some(),
+            _ => false,
+        }
+    }
+}
+
+// "rrggbb" hex, with or without a leading '#'
+pub fn parse_color(value: &str) -> Option<Color> {
+    let hex = value.trim_start_matches('#');
+    if hex.len() != 6 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+
+    let rgb = u32::from_str_radix(hex, 16).ok()?;
+    Some(Color::RGB((rgb >> 16) as u8, (rgb >> 8) as u8, rgb as u8))
+}
+
+fn format_color(color: Color) -> String {
+    format!("{:02x}{:02x}{:02x}", color.r, color.g, color.b)
+}
+
+fn settings_path() -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join(SETTINGS_DIR).join(SETTINGS_FILE))
+}